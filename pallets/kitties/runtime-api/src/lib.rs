@@ -0,0 +1,40 @@
+//! Kitties Pallet 的 Runtime API
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// 查询 Kitties 的接口
+	pub trait KittiesApi<AccountId, Balance, KittyIndex, KittyInfo, KittyRecord, MarketParams>
+	where
+		AccountId: Codec,
+		Balance: Codec,
+		KittyIndex: Codec,
+		KittyInfo: Codec,
+		KittyRecord: Codec,
+		MarketParams: Codec,
+	{
+		/// 账户拥有的 Kitty 编号
+		/// ### Arguments
+		/// * `owner` - 账户
+		fn kitties_by_owner(owner: AccountId) -> Vec<KittyIndex>;
+
+		/// 批量查询 Kitty 的信息, 结果与编号的顺序相同, 不存在的 Kitty 为 None
+		/// ### Arguments
+		/// * `kitty_ids` - Kitty 编号, 数量有上限, 超出的被忽略
+		fn kitties_info(kitty_ids: Vec<KittyIndex>) -> Vec<Option<KittyInfo>>;
+
+		/// Kitty 的完整公开记录, Kitty 不存在时为 None
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		fn kitty_record(kitty_id: KittyIndex) -> Option<KittyRecord>;
+
+		/// 市场参数, 用于前端显示价格和费用
+		fn market_params() -> MarketParams;
+
+		/// 市场累计的成交次数和成交金额
+		fn market_stats() -> (u32, Balance);
+	}
+}
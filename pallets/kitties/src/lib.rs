@@ -6,6 +6,9 @@
 
 pub use pallet::*;
 
+pub mod migrations;
+pub mod traits;
+
 #[cfg(test)]
 mod mock;
 
@@ -17,20 +20,139 @@ mod benchmarking;
 
 #[frame_support::pallet]
 pub mod pallet {
+	use crate::traits::{
+		AuthorizeTransfer, BreedMixer, Consumable, DnaHasher, KittyHooks, ValidatePrice,
+	};
 	use frame_support::{
 		dispatch::DispatchResult,
 		pallet_prelude::*,
-		traits::{Currency, Randomness, ReservableCurrency},
+		sp_std::{convert::TryFrom, prelude::*},
+		storage::bounded_vec::BoundedVec,
+		traits::{
+			tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+			Currency, ExistenceRequirement, Randomness, ReservableCurrency,
+		},
+		transactional, PalletId,
 	};
+	#[cfg(feature = "try-runtime")]
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
 	use frame_system::pallet_prelude::*;
+	#[cfg(feature = "std")]
+	use serde::{Deserialize, Serialize};
 	use sp_io::hashing::blake2_128;
-	use sp_runtime::traits::{AtLeast32BitUnsigned, Bounded};
+	use sp_runtime::{
+		traits::{AccountIdConversion, AtLeast32BitUnsigned, Bounded, Saturating, Zero},
+		Permill, TokenError,
+	};
+
+	/// DNA 重复时最多生成的次数
+	const MAX_DNA_ATTEMPTS: u32 = 8;
+
+	/// Kitty 的状态, 保存 `T::DnaLength` 字节的 DNA
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct Kitty(pub Vec<u8>);
+
+	/// 查询用的 Kitty 信息
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct KittyInfo<AccountId, AssetId, Balance> {
+		/// DNA
+		pub dna: Vec<u8>,
+		/// 主人
+		pub owner: AccountId,
+		/// 出售价格 (资产编号, 金额), 没有出售时为 None
+		pub price: Option<(AssetId, Balance)>,
+		/// 代数
+		pub generation: u32,
+	}
+
+	/// Kitty 的完整公开记录, 前端一次查询即可显示
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct KittyRecord<AccountId, AssetId, Balance, BlockNumber, KittyIndex> {
+		/// DNA
+		pub dna: Vec<u8>,
+		/// 主人
+		pub owner: AccountId,
+		/// 代数
+		pub generation: u32,
+		/// 父母的编号, 创建的 Kitty 为 None
+		pub parents: Option<(KittyIndex, KittyIndex)>,
+		/// 出售价格 (资产编号, 金额), 没有出售时为 None
+		pub price: Option<(AssetId, Balance)>,
+		/// 名字, 没有命名时为 None
+		pub name: Option<Vec<u8>>,
+		/// 是否处于锁定状态
+		pub locked: bool,
+		/// 出生的区块, 升级前创建的 Kitty 没有记录
+		pub created_at: Option<BlockNumber>,
+	}
+
+	/// 市场参数, 用于前端显示
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct MarketParams<Balance, BlockNumber> {
+		/// 余额的小数位数
+		pub decimals: u8,
+		/// 创建 Kitty 的质押, 免费创建时为 0
+		pub reserve: Balance,
+		/// 设置属性的质押
+		pub attribute_deposit: Balance,
+		/// 认领被遗弃 Kitty 的费用
+		pub claim_fee: Balance,
+		/// 购买后不能出售的区块数
+		pub sale_cooldown: BlockNumber,
+	}
+
+	/// 导出到其他链的 Kitty, 包含重建 Kitty 需要的数据
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct ExportedKitty<KittyIndex> {
+		/// DNA
+		pub dna: Vec<u8>,
+		/// 代数, 创建的 Kitty 为 0
+		pub generation: u32,
+		/// 父母的编号
+		pub parents: Option<(KittyIndex, KittyIndex)>,
+	}
+
+	/// 由 DNA 解码的特征, 每种特征对应 DNA 中的一个字节
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum TraitKind {
+		/// 毛色, 第 0 个字节
+		Color,
+		/// 眼睛, 第 1 个字节
+		Eyes,
+		/// 花纹, 第 2 个字节
+		Pattern,
+		/// 体型, 第 3 个字节
+		Size,
+	}
+
+	impl TraitKind {
+		/// 所有特征, 按在 DNA 中的位置排列
+		pub const ALL: [TraitKind; 4] =
+			[TraitKind::Color, TraitKind::Eyes, TraitKind::Pattern, TraitKind::Size];
 
-	/// Kitty 的状态
-	#[derive(Encode, Decode)]
-	pub struct Kitty(pub [u8; 16]);
+		/// 特征在 DNA 中的位置
+		pub fn gene_index(&self) -> usize {
+			match self {
+				TraitKind::Color => 0,
+				TraitKind::Eyes => 1,
+				TraitKind::Pattern => 2,
+				TraitKind::Size => 3,
+			}
+		}
+
+		/// 特征可取的值的数量, 突变后的值在 `0..variants` 之间
+		pub fn variants(&self) -> u8 {
+			match self {
+				TraitKind::Color => 16,
+				TraitKind::Eyes => 8,
+				TraitKind::Pattern => 12,
+				TraitKind::Size => 4,
+			}
+		}
+	}
 
-	type BalanceOf<T> =
+	pub(crate) type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 	#[pallet::config]
@@ -39,12 +161,229 @@ pub mod pallet {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 		/// 随机数模块
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+		/// DNA 的字节数
+		type DnaLength: Get<u32>;
+		/// 为 true 时 `KittyCreated` 事件不包含 DNA, 减小事件的大小
+		type CompactEvents: Get<bool>;
+		/// 第二随机数来源, 与 `Randomness` 的结果异或后使用
+		/// 不需要时配置为 `NoRandomness`
+		type SecondaryRandomness: Randomness<Self::Hash, Self::BlockNumber>;
 		/// Kitty 编号
 		type KittyIndex: Parameter + AtLeast32BitUnsigned + Default + Copy + Bounded;
 		/// 创建 Kitty 时需要质押的金额
 		type ReserveOfNewCreate: Get<BalanceOf<Self>>;
+		/// 创建 Kitty 时是否需要质押, 为 false 时免费创建
+		type RequireDeposit: Get<bool>;
+		/// 是否只允许 `Minters` 中的账户创建 Kitty
+		type RestrictMinting: Get<bool>;
 		/// 余额模块
 		type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+		/// 是否允许生产 Kitty
+		type BreedingEnabled: Get<bool>;
+		/// 生产 Kitty 时产下双胞胎的概率
+		type TwinRate: Get<Permill>;
+		/// 生产 Kitty 时发生特征突变的概率
+		/// 突变时随机选择一个特征, 替换为新的随机特征值, 其它基因不变
+		type TraitMutationRate: Get<Permill>;
+		/// 孩子偏向父亲的程度
+		/// 随机数的每个字节 r 小于 bias 时, 孩子的这个字节来自父亲, 否则来自母亲
+		/// 0 时完全来自母亲, 128 时各占一半, 255 时几乎完全来自父亲
+		type InheritanceBias: Get<u8>;
+		/// 每个区块最多允许生产的次数
+		type MaxBreedsPerBlock: Get<u32>;
+		/// 生产后父母需要休息的基础区块数, 实际为 `base * (1 + generation)`
+		type BaseBreedCooldown: Get<Self::BlockNumber>;
+		/// 父母可以生产的最小年龄, 单位为区块
+		type MinBreedAge: Get<Self::BlockNumber>;
+		/// 生产时父母是否必须属于同一品种
+		type RequireMatchingBreeds: Get<bool>;
+		/// 是否允许融合 Kitty
+		type FusionEnabled: Get<bool>;
+		/// 生产 Kitty 时混合父母 DNA 的算法, 默认为 `traits::BitwiseBlend`
+		type BreedAlgorithm: BreedMixer;
+		/// 生成 DNA 时使用的哈希算法, 默认为 `traits::Blake2Dna`
+		type DnaHasher: DnaHasher;
+		/// Kitty 创建、转移、销毁时的回调, 不需要时设为 `()`
+		type OnKittyEvent: KittyHooks<Self::AccountId, Self::KittyIndex>;
+		/// 出售时检查价格, 不需要时设为 `()`
+		type PriceValidator: ValidatePrice<BalanceOf<Self>, Self::KittyIndex>;
+		/// 生产时消耗的道具, 不需要时设为 `()`
+		type BreedConsumable: Consumable<Self::AccountId>;
+		/// 转移前的外部审批, 不需要时设为 `()`
+		type TransferAuthorizer: AuthorizeTransfer<Self::AccountId, Self::KittyIndex>;
+		/// 管理员权限
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+		/// 属性名的最大长度
+		type MaxKeyLen: Get<u32>;
+		/// 属性值的最大长度
+		type MaxValueLen: Get<u32>;
+		/// 每个属性需要质押的金额
+		type AttributeDeposit: Get<BalanceOf<Self>>;
+		/// Kitty 被购买后, 需要经过的区块数才能再次出售
+		type SaleCooldown: Get<Self::BlockNumber>;
+		/// 批量操作的最大数量
+		type MaxBatchSize: Get<u32>;
+		/// 每个账户最多拥有的 Kitty 数量
+		type MaxKittiesPerAccount: Get<u32>;
+		/// Pallet 的编号, 用于生成收取费用的账户
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// 主人超过多少个区块没有操作时, 其他人可以认领他的 Kitty
+		type AbandonmentPeriod: Get<Self::BlockNumber>;
+		/// 认领 Kitty 的费用, 支付给 Pallet 的账户
+		type AbandonmentClaimFee: Get<BalanceOf<Self>>;
+		/// 余额的小数位数, 用于前端显示价格
+		#[pallet::constant]
+		type BalanceDecimals: Get<u8>;
+		/// Kitty 名字的最大长度
+		type MaxNameLength: Get<u32>;
+		/// 是否拒绝转让给不存在的账户, 防止地址输错导致 Kitty 丢失
+		type RequireRecipientExists: Get<bool>;
+		/// 是否要求 Kitty 的名字不重复
+		type RequireUniqueNames: Get<bool>;
+		/// `transfer` 时是否发出 `KittyTransfered` 事件, 购买和认领总是发出
+		type EmitTransferEvents: Get<bool>;
+		/// 每个账户为 Kitty 质押的最大总额
+		type MaxDepositPerAccount: Get<BalanceOf<Self>>;
+		/// 传说 Kitty 能否生产, 为 true 时可以生产且没有休息期
+		type LegendaryBypassCooldown: Get<bool>;
+		/// 出售 Kitty 时使用的资产编号, 0 表示原生的 `T::Currency`
+		type AssetId: Parameter + AtLeast32BitUnsigned + Default + Copy;
+		/// 以其他资产出售时用于支付的资产模块, 不会收到资产 0 的转账
+		/// 只使用原生货币时配置为 `NoAssets`
+		type Assets: fungibles::Transfer<
+			Self::AccountId,
+			AssetId = Self::AssetId,
+			Balance = BalanceOf<Self>,
+		>;
+		/// 每次生产奖励给生产者的新铸造的金额, 为 0 时没有奖励
+		type BreedReward: Get<BalanceOf<Self>>;
+		/// 生产奖励累计铸造的上限, 达到后不再奖励, 生产照常进行
+		type MaxBreedRewards: Get<BalanceOf<Self>>;
+		/// `KittiesCount` 达到这个值时发出一次 `CountThresholdReached` 事件, 提醒编号即将用尽
+		type CountWarningThreshold: Get<Self::KittyIndex>;
+		/// 生产时支付给 Pallet 账户的手续费, 为 0 时免费生产
+		type BreedFee: Get<BalanceOf<Self>>;
+		/// 父母每生产一次, 生产手续费增加的比例, 按生产次数较多的父母计算
+		type BreedCostScaling: Get<Permill>;
+		/// `RecentKitties` 保存的最近创建的 Kitty 数量
+		type RecentWindow: Get<u32>;
+		/// 是否允许出售中的 Kitty 生产
+		type AllowBreedingListed: Get<bool>;
+		/// 获得 Kitty 后多少个区块内不能转让或出售, 为 0 时不限制
+		type PostAcquireTransferCooldown: Get<Self::BlockNumber>;
+		/// 可以记录浏览次数的预言机
+		type ViewOracle: EnsureOrigin<Self::Origin>;
+		/// 每个 Cattery 最多包含的 Kitty 数量
+		type MaxCatterySize: Get<u32>;
+		/// 每个账户每个区块最多允许生产的次数
+		type MaxBreedsPerAccountPerBlock: Get<u32>;
+		/// 托管购买的审核期, 购买者只能在期内确认, 期满后任何人都可以取消并退款
+		type EscrowPeriod: Get<Self::BlockNumber>;
+		/// 每个区块最多到期的定时锁定数量
+		type MaxLockExpiriesPerBlock: Get<u32>;
+		/// 创建 Cattery 时需要质押的金额, 删除 Cattery 时退还
+		type CatteryDeposit: Get<BalanceOf<Self>>;
+	}
+
+	/// 存储的版本
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum Releases {
+		/// 初始版本
+		V1,
+		/// `Owner` 改为 `OptionQuery`
+		V2,
+		/// `Kitty` 的 DNA 改为可变长度
+		V3,
+		/// 增加 `DnaIndex`
+		V4,
+		/// 增加 `OwnedKitties`
+		V5,
+		/// 增加 `OwnedKittiesCount`
+		V6,
+		/// 增加 `GenerationCounts`
+		V7,
+		/// 为记录质押之前创建的 Kitty 补充 `KittyDeposits`
+		V8,
+		/// 增加 `AccountDeposits`
+		V9,
+		/// `KittiesPrice` 的价格增加资产编号
+		V10,
+		/// `EscrowedPurchases` 增加托管开始的区块
+		V11,
+	}
+
+	impl Default for Releases {
+		fn default() -> Self {
+			Releases::V1
+		}
+	}
+
+	/// 空的随机数来源, 总是返回默认值
+	/// 用作 `SecondaryRandomness` 时, 等同于只使用 `Randomness`
+	pub struct NoRandomness;
+
+	impl<Output: Default, BlockNumber: Default> Randomness<Output, BlockNumber> for NoRandomness {
+		fn random(_subject: &[u8]) -> (Output, BlockNumber) {
+			(Default::default(), Default::default())
+		}
+	}
+
+	/// 没有其他资产的资产模块, 所有余额为 0, 转账总是失败
+	/// 用作 `Assets` 时, 只能以原生货币出售
+	pub struct NoAssets<AssetId, Balance>(PhantomData<(AssetId, Balance)>);
+
+	impl<AccountId, AssetId, Balance> fungibles::Inspect<AccountId> for NoAssets<AssetId, Balance>
+	where
+		AssetId: frame_support::traits::tokens::AssetId,
+		Balance: frame_support::traits::tokens::Balance,
+	{
+		type AssetId = AssetId;
+		type Balance = Balance;
+
+		fn total_issuance(_asset: AssetId) -> Balance {
+			Zero::zero()
+		}
+
+		fn minimum_balance(_asset: AssetId) -> Balance {
+			Zero::zero()
+		}
+
+		fn balance(_asset: AssetId, _who: &AccountId) -> Balance {
+			Zero::zero()
+		}
+
+		fn reducible_balance(_asset: AssetId, _who: &AccountId, _keep_alive: bool) -> Balance {
+			Zero::zero()
+		}
+
+		fn can_deposit(_asset: AssetId, _who: &AccountId, _amount: Balance) -> DepositConsequence {
+			DepositConsequence::UnknownAsset
+		}
+
+		fn can_withdraw(
+			_asset: AssetId,
+			_who: &AccountId,
+			_amount: Balance,
+		) -> WithdrawConsequence<Balance> {
+			WithdrawConsequence::UnknownAsset
+		}
+	}
+
+	impl<AccountId, AssetId, Balance> fungibles::Transfer<AccountId> for NoAssets<AssetId, Balance>
+	where
+		AssetId: frame_support::traits::tokens::AssetId,
+		Balance: frame_support::traits::tokens::Balance,
+	{
+		fn transfer(
+			_asset: AssetId,
+			_source: &AccountId,
+			_dest: &AccountId,
+			_amount: Balance,
+			_keep_alive: bool,
+		) -> Result<Balance, DispatchError> {
+			Err(TokenError::UnknownAsset.into())
+		}
 	}
 
 	#[pallet::pallet]
@@ -56,11 +395,16 @@ pub mod pallet {
 	#[pallet::getter(fn kitties_count)]
 	pub type KittiesCount<T: Config> = StorageValue<_, T::KittyIndex>;
 
-	/// Kitties 价格表
+	/// Kitties 价格表, 价格为 (资产编号, 金额)
 	#[pallet::storage]
 	#[pallet::getter(fn kitties_price)]
-	pub type KittiesPrice<T: Config> =
-		StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<BalanceOf<T>>, ValueQuery>;
+	pub type KittiesPrice<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::KittyIndex,
+		Option<(T::AssetId, BalanceOf<T>)>,
+		ValueQuery,
+	>;
 
 	/// Kitties
 	#[pallet::storage]
@@ -71,21 +415,361 @@ pub mod pallet {
 	/// Kitties 的主人
 	#[pallet::storage]
 	#[pallet::getter(fn owner)]
-	pub type Owner<T: Config> =
-		StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<T::AccountId>, ValueQuery>;
+	pub type Owner<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId>;
+
+	/// 清理遗留数据时最后检查的 Kitty 编号
+	#[pallet::storage]
+	#[pallet::getter(fn orphan_sweep_cursor)]
+	pub type OrphanSweepCursor<T: Config> = StorageValue<_, T::KittyIndex>;
+
+	/// 存储的版本, 用于判断是否需要迁移
+	#[pallet::storage]
+	pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+	/// Kitties 创建时质押的金额
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_deposit)]
+	pub type KittyDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, BalanceOf<T>, ValueQuery>;
+
+	/// 每个账户持有的 Kitties 的质押总额
+	#[pallet::storage]
+	#[pallet::getter(fn account_deposit)]
+	pub type AccountDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// Kitties 出生的区块
+	#[pallet::storage]
+	#[pallet::getter(fn birth_block)]
+	pub type BirthBlock<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+	/// Kitties 的自定义属性
+	#[pallet::storage]
+	#[pallet::getter(fn attributes)]
+	pub type Attributes<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::KittyIndex,
+		Blake2_128Concat,
+		BoundedVec<u8, T::MaxKeyLen>,
+		BoundedVec<u8, T::MaxValueLen>,
+	>;
+
+	/// 属性的质押 [depositor, deposit]
+	#[pallet::storage]
+	pub type AttributeDeposits<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::KittyIndex,
+		Blake2_128Concat,
+		BoundedVec<u8, T::MaxKeyLen>,
+		(T::AccountId, BalanceOf<T>),
+	>;
+
+	/// Kitties 最近一次被购买的区块
+	#[pallet::storage]
+	#[pallet::getter(fn last_sale_block)]
+	pub type LastSaleBlock<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+	/// 市场累计成交次数
+	#[pallet::storage]
+	#[pallet::getter(fn total_sales_count)]
+	pub type TotalSalesCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// 市场累计成交金额, 只统计以原生货币成交的金额
+	#[pallet::storage]
+	#[pallet::getter(fn total_volume)]
+	pub type TotalVolume<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// 最近创建的 Kitties, 按创建顺序排列, 超过 `T::RecentWindow` 时移除最早的一个
+	/// 包括创建、生产、导入等所有方式产生的 Kitty, 销毁后不会移除
+	#[pallet::storage]
+	#[pallet::getter(fn recent_kitties)]
+	pub type RecentKitties<T: Config> =
+		StorageValue<_, BoundedVec<T::KittyIndex, T::RecentWindow>, ValueQuery>;
+
+	/// Kitty 最近一次被转让、购买或认领的区块
+	#[pallet::storage]
+	#[pallet::getter(fn acquired_at)]
+	pub type AcquiredAt<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+	/// Cattery 的主人、名字和包含的 Kitties
+	#[pallet::storage]
+	#[pallet::getter(fn catteries)]
+	pub type Catteries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		u32,
+		(
+			T::AccountId,
+			BoundedVec<u8, T::MaxNameLength>,
+			BoundedVec<T::KittyIndex, T::MaxCatterySize>,
+		),
+	>;
+
+	/// 下一个 Cattery 的编号
+	#[pallet::storage]
+	#[pallet::getter(fn next_cattery_id)]
+	pub type NextCatteryId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// 创建 Cattery 时质押的金额
+	#[pallet::storage]
+	#[pallet::getter(fn cattery_deposit)]
+	pub type CatteryDeposits<T: Config> =
+		StorageMap<_, Twox64Concat, u32, BalanceOf<T>, ValueQuery>;
+
+	/// Kitty 所在的 Cattery, 每个 Kitty 最多在一个 Cattery 中
+	#[pallet::storage]
+	#[pallet::getter(fn cattery_of)]
+	pub type KittyCattery<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, u32>;
+
+	/// Kitty 的浏览次数, 由预言机记录
+	#[pallet::storage]
+	#[pallet::getter(fn view_count)]
+	pub type ViewCounts<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, u64, ValueQuery>;
+
+	/// Kitty 作为父母生产的次数
+	#[pallet::storage]
+	#[pallet::getter(fn breed_count)]
+	pub type BreedCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, u32, ValueQuery>;
+
+	/// 是否已经发出 `CountThresholdReached` 事件
+	#[pallet::storage]
+	#[pallet::getter(fn warning_emitted)]
+	pub type WarningEmitted<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// 累计铸造的生产奖励
+	#[pallet::storage]
+	#[pallet::getter(fn total_breed_rewards)]
+	pub type TotalBreedRewards<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// 当前区块已经生产的次数, 每个区块开始时清零
+	#[pallet::storage]
+	#[pallet::getter(fn breeds_this_block)]
+	pub type BreedsThisBlock<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// 账户最近一次生产的区块和该区块内生产的次数
+	#[pallet::storage]
+	#[pallet::getter(fn account_breeds)]
+	pub type AccountBreeds<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, u32), ValueQuery>;
+
+	/// 被锁定的 Kitties
+	/// None 表示一直锁定, Some 表示锁定到指定区块
+	#[pallet::storage]
+	#[pallet::getter(fn locked_kitties)]
+	pub type LockedKitties<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<T::BlockNumber>>;
+
+	/// 每个区块到期的锁定, 解除或重新锁定时移除
+	#[pallet::storage]
+	pub type LockExpiries<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<T::KittyIndex, T::MaxLockExpiriesPerBlock>,
+		ValueQuery,
+	>;
+
+	/// 质押中的 Kitties 和开始质押的区块
+	#[pallet::storage]
+	#[pallet::getter(fn staked_since)]
+	pub type Staked<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+	/// 托管中的购买, 记录购买者、托管的货款和托管开始的区块
+	#[pallet::storage]
+	#[pallet::getter(fn escrowed_purchase)]
+	pub type EscrowedPurchases<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::KittyIndex,
+		(T::AccountId, BalanceOf<T>, T::BlockNumber),
+	>;
+
+	/// 传说 Kitties, 只能由管理员创建, 除非 `T::LegendaryBypassCooldown` 为 true, 否则不能生产
+	#[pallet::storage]
+	pub type LegendaryKitties<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, ()>;
+
+	/// 每个账户登记的父母
+	#[pallet::storage]
+	#[pallet::getter(fn breeding_pairs)]
+	pub type BreedingPairs<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::KittyIndex, T::KittyIndex)>;
+
+	/// 每个账户拥有的 Kitty
+	#[pallet::storage]
+	pub type OwnedKitties<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::KittyIndex,
+		(),
+		OptionQuery,
+	>;
+
+	/// Kitty 的父母, 创建的 Kitty 没有父母
+	#[pallet::storage]
+	#[pallet::getter(fn parents)]
+	pub type Parents<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, (T::KittyIndex, T::KittyIndex)>;
+
+	/// Kitty 的代数, 孩子比父母中较大的代数多 1
+	#[pallet::storage]
+	#[pallet::getter(fn generation)]
+	pub type Generation<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, u32, ValueQuery>;
+
+	/// 每一代 Kitty 的数量
+	#[pallet::storage]
+	#[pallet::getter(fn generation_count)]
+	pub type GenerationCounts<T: Config> = StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
+
+	/// Kitty 在这个区块之前不能生产
+	#[pallet::storage]
+	#[pallet::getter(fn breed_cooldown_until)]
+	pub type BreedCooldownUntil<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber>;
+
+	/// Kitty 的品种
+	#[pallet::storage]
+	#[pallet::getter(fn breed_tag)]
+	pub type BreedTag<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, u8>;
+
+	/// 每个账户最后一次操作 Kitty 的区块
+	#[pallet::storage]
+	#[pallet::getter(fn last_activity)]
+	pub type LastActivity<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+	/// 每个账户拥有的 Kitty 数量
+	#[pallet::storage]
+	#[pallet::getter(fn owned_kitties_count)]
+	pub type OwnedKittiesCount<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// 每个账户获得过的 Kitty 数量
+	#[pallet::storage]
+	#[pallet::getter(fn owner_kitty_seq)]
+	pub type OwnerKittySeq<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Kitty 是主人获得的第几个 Kitty, 从 1 开始
+	#[pallet::storage]
+	#[pallet::getter(fn owner_ordinal)]
+	pub type OwnerOrdinal<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::KittyIndex,
+		u32,
+		OptionQuery,
+	>;
+
+	/// 允许创建 Kitty 的账户
+	#[pallet::storage]
+	#[pallet::getter(fn minters)]
+	pub type Minters<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+	/// DNA 哈希到 Kitty 编号的索引, 保证 DNA 不重复
+	#[pallet::storage]
+	#[pallet::getter(fn dna_index)]
+	pub type DnaIndex<T: Config> = StorageMap<_, Identity, [u8; 16], T::KittyIndex>;
+
+	/// 正在调用 `T::OnKittyEvent` 的回调, 只在回调期间为 true
+	#[pallet::storage]
+	pub type ReentrancyGuard<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+	/// Kitty 的名字
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_name)]
+	pub type KittyNames<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, BoundedVec<u8, T::MaxNameLength>>;
+
+	/// 名字对应的 Kitty, 名字重复时为最后使用该名字的 Kitty
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_by_name)]
+	pub type NameIndex<T: Config> =
+		StorageMap<_, Blake2_128Concat, BoundedVec<u8, T::MaxNameLength>, T::KittyIndex>;
 
 	#[pallet::event]
 	#[pallet::metadata(T::AccountId = "AccountId")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		/// 创建成功 [account, kitty_id]
-		KittyCreated(T::AccountId, T::KittyIndex),
+		/// 创建成功 [account, kitty_id, dna], `T::CompactEvents` 为 true 时 dna 为 None
+		KittyCreated(T::AccountId, T::KittyIndex, Option<Vec<u8>>),
 		/// 转让成功 [who, receiver, kitty_id]
 		KittyTransfered(T::AccountId, T::AccountId, T::KittyIndex),
-		/// 发起出售 [who, kitty_id, price]
-		KittyForSale(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
+		/// 发起出售 [who, kitty_id, (asset_id, price)]
+		KittyForSale(T::AccountId, T::KittyIndex, Option<(T::AssetId, BalanceOf<T>)>),
 		/// 取消出售 [account, kitty_id]
 		KittyCancelSale(T::AccountId, T::KittyIndex),
+		/// 修改出售价格 [who, kitty_id, (old_asset_id, old_price), (new_asset_id, new_price)]
+		KittyPriceChanged(
+			T::AccountId,
+			T::KittyIndex,
+			(T::AssetId, BalanceOf<T>),
+			(T::AssetId, BalanceOf<T>),
+		),
+		/// 批量转让账户的 Kitties, skipped 为不能转让而被跳过的数量, next 为继续转让的起点
+		/// [from, to, transferred, skipped, next]
+		KittiesTransferredAll(T::AccountId, T::AccountId, u32, u32, Option<T::KittyIndex>),
+		/// 重置 Kitties 总数 [kitties_count]
+		KittiesCountReset(T::KittyIndex),
+		/// Kitties 总数达到 `CountWarningThreshold`, 只发出一次 [kitties_count]
+		CountThresholdReached(T::KittyIndex),
+		/// 销毁成功 [who, kitty_id]
+		KittyBurned(T::AccountId, T::KittyIndex),
+		/// 设置属性 [kitty_id, key, value]
+		AttributeSet(T::KittyIndex, Vec<u8>, Vec<u8>),
+		/// 清除属性 [kitty_id, key]
+		AttributeCleared(T::KittyIndex, Vec<u8>),
+		/// 索引快照标记 [block_number]
+		SnapshotMarker(T::BlockNumber),
+		/// 设置名字 [kitty_id, name]
+		KittyNamed(T::KittyIndex, Vec<u8>),
+		/// 设置品种 [kitty_id, tag]
+		BreedTagSet(T::KittyIndex, u8),
+		/// 添加创建者 [account]
+		MinterAdded(T::AccountId),
+		/// 移除创建者 [account]
+		MinterRemoved(T::AccountId),
+		/// 认领被遗弃的 Kitty [claimer, kitty_id]
+		KittyClaimed(T::AccountId, T::KittyIndex),
+		/// 货款已托管 [buyer, kitty_id, price]
+		PurchaseEscrowed(T::AccountId, T::KittyIndex, BalanceOf<T>),
+		/// 托管的购买已确认 [buyer, kitty_id]
+		PurchaseConfirmed(T::AccountId, T::KittyIndex),
+		/// 托管的购买已取消, 货款退还 [buyer, kitty_id]
+		PurchaseCancelled(T::AccountId, T::KittyIndex),
+		/// 管理员解除质押 [who, amount]
+		DepositUnreserved(T::AccountId, BalanceOf<T>),
+		/// 生产者获得奖励 [who, amount]
+		BreedRewardPaid(T::AccountId, BalanceOf<T>),
+		/// 创建了 Cattery [owner, cattery_id]
+		CatteryCreated(T::AccountId, u32),
+		/// Kitty 加入 Cattery [cattery_id, kitty_id]
+		KittyAddedToCattery(u32, T::KittyIndex),
+		/// Kitty 移出 Cattery [cattery_id, kitty_id]
+		KittyRemovedFromCattery(u32, T::KittyIndex),
+		/// 删除了 Cattery, 质押已退还 [owner, cattery_id]
+		CatteryRemoved(T::AccountId, u32),
+		/// 预言机记录了浏览次数 [updates]
+		ViewsRecorded(u32),
+		/// 管理员提取 Pallet 账户中的手续费 [to, amount]
+		FeesWithdrawn(T::AccountId, BalanceOf<T>),
+		/// Kitty 的锁定状态改变 [kitty_id, locked]
+		KittyLockChanged(T::KittyIndex, bool),
+		/// Kitty 开始质押 [owner, kitty_id]
+		KittyStaked(T::AccountId, T::KittyIndex),
+		/// Kitty 解除质押 [owner, kitty_id]
+		KittyUnstaked(T::AccountId, T::KittyIndex),
+		/// 重建账户拥有的 Kitty 索引 [account, count]
+		OwnedListRepaired(T::AccountId, u32),
 	}
 
 	// Errors inform users that something went wrong.
@@ -107,43 +791,317 @@ pub mod pallet {
 		KittyAlreadyOwned,
 		/// 相同的拥有者
 		SameOwner,
+		/// Kitty 已被锁定
+		KittyLocked,
+		/// 锁定的到期区块必须大于当前区块
+		InvalidLockExpiry,
+		/// 不允许生产 Kitty
+		BreedingDisabled,
+		/// Kitties 总数不能小于当前值
+		KittiesCountTooLow,
+		/// 属性名过长
+		AttributeKeyTooLong,
+		/// 属性值过长
+		AttributeValueTooLong,
+		/// 属性不存在
+		AttributeNotFound,
+		/// Kitty 刚被购买, 冷却期内不能出售
+		SaleCooldownActive,
+		/// 生产次数达到上限
+		BreedLimitReached,
+		/// 批量操作的数量超过上限
+		TooManyKitties,
+		/// 批量操作中有重复的 Kitty 编号
+		DuplicateKittyIndex,
+		/// 出售 Kitty 的账户已被删除
+		SellerGone,
+		/// 没有登记父母
+		NoBreedingPair,
+		/// 名字太长
+		NameTooLong,
+		/// 多次重新生成后 DNA 仍然重复
+		DuplicateDna,
+		/// 父母的品种不同
+		IncompatibleBreeds,
+		/// 在回调中重入修改 Kitty 归属
+		Reentrant,
+		/// `T::ReserveOfNewCreate` 小于最小余额
+		InvalidDepositConfig,
+		/// 不允许融合 Kitty
+		FusionDisabled,
+		/// 不允许创建 Kitty
+		NotAuthorizedToMint,
+		/// 价格高于购买者愿意支付的最高价格
+		PriceExceedsMax,
+		/// 接收者拥有的 Kitty 已达上限
+		RecipientAtCapacity,
+		/// Kitty 没有被遗弃
+		NotAbandoned,
+		/// 父母还在生产后的休息期
+		BreedCooldownActive,
+		/// 父母的年龄小于 `MinBreedAge`
+		KittyTooYoung,
+		/// Kitty 质押中
+		KittyStaked,
+		/// Kitty 没有质押
+		NotStaked,
+		/// 接收者账户不存在
+		RecipientDoesNotExist,
+		/// 传说 Kitty 不能生产
+		LegendaryCannotBreed,
+		/// Kitty 的数量超过扫描上限
+		RepairScanExceeded,
+		/// 名字已被其他 Kitty 使用
+		NameTaken,
+		/// 账户的质押总额超过 `MaxDepositPerAccount`
+		DepositCapExceeded,
+		/// Kitty 已有托管中的购买
+		EscrowPending,
+		/// Kitty 没有托管中的购买
+		NoEscrow,
+		/// 不是托管购买的购买者
+		NotEscrowBuyer,
+		/// Pallet 账户中可提取的手续费不足
+		InsufficientTreasuryBalance,
+		/// 出售使用的资产与购买时指定的资产不同
+		AssetMismatch,
+		/// 托管购买只支持以原生货币出售的 Kitty
+		AssetNotSupported,
+		/// 父母正在出售, 不能生产
+		ParentListedForSale,
+		/// 获得 Kitty 后的冷却期内不能转让或出售
+		TransferCooldownActive,
+		/// Cattery 不存在
+		CatteryNotFound,
+		/// 不是 Cattery 的主人
+		NotCatteryOwner,
+		/// Cattery 的 Kitty 数量达到 `MaxCatterySize`
+		CatteryFull,
+		/// Kitty 已经在 Cattery 中
+		AlreadyInCattery,
+		/// Kitty 不在 Cattery 中
+		NotInCattery,
+		/// Cattery 编号溢出
+		CatteriesCountOverflow,
+		/// DNA 的长度不是 `T::DnaLength` 字节
+		InvalidDnaLength,
+		/// 托管购买的审核期已满, 不能再确认
+		EscrowExpired,
+		/// 审核期满前只有购买者和卖家可以取消托管的购买
+		EscrowNotExpired,
+		/// 同一区块到期的锁定达到 `T::MaxLockExpiriesPerBlock`
+		TooManyLockExpiries,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// 创世时创建的 Kitty 的主人和 DNA, 按顺序分配编号, 不需要质押
+		pub kitties: Vec<(T::AccountId, Vec<u8>)>,
+		/// 为 true 时 DNA 为空的 Kitty 由主人和编号生成 DNA
+		pub derive_dna: bool,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { kitties: Vec::new(), derive_dna: false }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			StorageVersion::<T>::put(Releases::V11);
+
+			for (owner, dna) in &self.kitties {
+				let kitty_id = Pallet::<T>::next_kitty_id()
+					.expect("genesis kitties must fit in the kitty index range");
+				let dna = if dna.is_empty() && self.derive_dna {
+					Pallet::<T>::genesis_dna(owner, kitty_id)
+				} else {
+					dna.clone()
+				};
+
+				assert_eq!(
+					dna.len(),
+					T::DnaLength::get() as usize,
+					"genesis kitty DNA must be DnaLength bytes"
+				);
+				assert!(
+					!DnaIndex::<T>::contains_key(Pallet::<T>::dna_hash(&dna)),
+					"genesis kitty DNA must be unique"
+				);
+
+				Pallet::<T>::mint(owner, kitty_id, dna, Zero::zero());
+			}
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = T::DbWeight::get().reads(1);
+
+			if StorageVersion::<T>::get() == Releases::V1 {
+				weight = weight.saturating_add(crate::migrations::v2::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V2 {
+				weight = weight.saturating_add(crate::migrations::v3::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V3 {
+				weight = weight.saturating_add(crate::migrations::v4::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V4 {
+				weight = weight.saturating_add(crate::migrations::v5::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V5 {
+				weight = weight.saturating_add(crate::migrations::v6::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V6 {
+				weight = weight.saturating_add(crate::migrations::v7::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V7 {
+				weight = weight.saturating_add(crate::migrations::v8::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V8 {
+				weight = weight.saturating_add(crate::migrations::v9::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V9 {
+				weight = weight.saturating_add(crate::migrations::v10::migrate::<T>());
+			}
+
+			if StorageVersion::<T>::get() == Releases::V10 {
+				weight = weight.saturating_add(crate::migrations::v11::migrate::<T>());
+			}
+
+			weight
+		}
+
+		/// 记录迁移前有主人的 Kitty 数量
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			Self::set_temp_storage(crate::migrations::owned_kitties_before::<T>(), "owned_kitties");
+			Ok(())
+		}
+
+		/// 检查迁移后 Kitty 的数量和所有权索引
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			let before =
+				Self::get_temp_storage("owned_kitties").ok_or("missing pre_upgrade count")?;
+			crate::migrations::check_owned_kitties::<T>(before)
+		}
+
+		/// 使用剩余的权重清理已销毁 Kitty 的遗留数据
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::sweep_orphans(remaining_weight)
+		}
+
+		/// 清除到期的锁定, 重置当前区块的生产次数
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			BreedsThisBlock::<T>::kill();
+
+			let expiries = LockExpiries::<T>::take(n);
+			let count = expiries.len() as Weight;
+			let mut cleared: Weight = 0;
+
+			for kitty_id in expiries {
+				// 锁定可能已被解除或重新设置, 只清除在当前区块到期的锁定
+				if Self::locked_kitties(kitty_id) == Some(Some(n)) {
+					LockedKitties::<T>::remove(kitty_id);
+					Self::deposit_event(Event::KittyLockChanged(kitty_id, false));
+					cleared += 1;
+				}
+			}
+
+			T::DbWeight::get().reads_writes(1 + count, 2 + 2 * cleared)
+		}
+
+		/// 检查创建质押不小于最小余额
+		fn integrity_test() {
+			assert!(
+				Self::creation_deposit().is_ok(),
+				"ReserveOfNewCreate must not be less than the existential deposit"
+			);
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// 创建 Kitty
-		/// 创建时需要质押一定的金额: `T::ReserveOfNewCreate`
+		/// 创建时需要质押一定的金额: `T::ReserveOfNewCreate`, `T::RequireDeposit` 为 false 时不质押
+		/// `T::RestrictMinting` 为 true 时只有 `Minters` 中的账户可以创建
 		/// ### Arguments
 		/// * `origin` - 创建者
 		#[pallet::weight(0)]
 		pub fn create(origin: OriginFor<T>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
 
-			let kitty_id = match Self::kitties_count() {
-				Some(id) => {
-					ensure!(id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-					id + 1u32.into()
-				}
-				None => 1u32.into(),
-			};
+			ensure!(
+				!T::RestrictMinting::get() || Self::minters(&who),
+				Error::<T>::NotAuthorizedToMint
+			);
+			ensure!(Self::can_receive(&who, 1), Error::<T>::RecipientAtCapacity);
 
-			// 扣除质押金额
-			T::Currency::reserve(&who, T::ReserveOfNewCreate::get())
-				.map_err(|_| Error::<T>::NotEnoughBalance)?;
+			let kitty_id = Self::next_kitty_id()?;
 
-			let dna = Self::random_value(&who);
+			let (_, dna) =
+				Self::unique_dna(Self::random_value(&who), |selector| selector.to_vec())?;
 
-			Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
-			Owner::<T>::insert(kitty_id, Some(&who));
-			KittiesCount::<T>::put(kitty_id);
+			// 扣除质押金额, 质押后的可用余额不能低于最小余额, 避免账户被回收
+			let deposit = Self::creation_deposit()?;
+			Self::ensure_deposit_cap(&who, deposit)?;
+			if !deposit.is_zero() {
+				Self::ensure_reserve_keeps_alive(&who, deposit)?;
+				T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::NotEnoughBalance)?;
+			}
+
+			Self::mint(&who, kitty_id, dna, deposit);
+
+			Ok(())
+		}
+
+		/// 管理员为账户创建 Kitty, 不需要质押, 不受 `T::RestrictMinting` 限制
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `owner` - Kitty 的主人
+		/// * `legendary` - 是否为传说 Kitty, 传说 Kitty 不能生产
+		#[pallet::weight(0)]
+		pub fn force_create(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			legendary: bool,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			Self::ensure_not_reentrant()?;
 
-			Self::deposit_event(Event::KittyCreated(who, kitty_id));
+			ensure!(Self::can_receive(&owner, 1), Error::<T>::RecipientAtCapacity);
+
+			let kitty_id = Self::next_kitty_id()?;
+
+			let (_, dna) =
+				Self::unique_dna(Self::random_value(&owner), |selector| selector.to_vec())?;
+
+			if legendary {
+				LegendaryKitties::<T>::insert(kitty_id, ());
+			}
+			Self::mint(&owner, kitty_id, dna, Zero::zero());
 
 			Ok(())
 		}
 
-		/// 转让 Kitty
+		/// 转让 Kitty, 出售中的 Kitty 会被下架
 		/// 转让者与接收者不能相同
+		/// Kitty 的质押金额随之转移: 接收者质押, 转让者解除质押
 		/// ### Arguments
 		/// * `origin` - 转让者
 		/// * `to` - 接收者
@@ -155,70 +1113,273 @@ pub mod pallet {
 			kitty_id: T::KittyIndex,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
 
-			ensure!(sender != to, Error::<T>::SameOwner);
+			Self::can_transfer(&sender, &to, kitty_id)?;
 
-			let owner = Owner::<T>::get(&kitty_id).unwrap();
-			ensure!(owner == sender, Error::<T>::NotOwnerOfKitty);
+			Self::move_deposit(&sender, &to, kitty_id)?;
+			Self::cancel_sale(&sender, kitty_id);
 
-			Self::transfer_kitty(sender, to, kitty_id);
+			Self::transfer_kitty(sender, to, kitty_id, T::EmitTransferEvents::get());
 			Ok(())
 		}
 
-		/// 生产 Kitty
-		/// 父母的编号不能相同
+		/// 转让 Kitty 并保留出售价格, 例如转给寄售账户继续出售
+		/// 转让后出售由接收者控制: 接收者可以修改或取消价格, 购买的货款支付给接收者,
+		/// 转让者不再有任何权利, 只应转给信任的账户
 		/// ### Arguments
-		/// * `origin` - 生产者
-		/// * `kitty_id_1` - 父亲的编号
-		/// * `kitty_id_2` - 母亲的编号
+		/// * `origin` - 转让者
+		/// * `to` - 接收者
+		/// * `kitty_id` - 转让的 Kitty 编号
 		#[pallet::weight(0)]
-		pub fn breed(
+		pub fn transfer_keep_listing(
 			origin: OriginFor<T>,
-			kitty_id_1: T::KittyIndex,
-			kitty_id_2: T::KittyIndex,
+			to: T::AccountId,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			Self::can_transfer(&sender, &to, kitty_id)?;
+
+			Self::move_deposit(&sender, &to, kitty_id)?;
+
+			Self::transfer_kitty(sender, to, kitty_id, T::EmitTransferEvents::get());
+			Ok(())
+		}
+
+		/// 设置 Kitty 的名字
+		/// `T::RequireUniqueNames` 为 true 时不能使用其他 Kitty 的名字, 原来的名字被释放
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		/// * `name` - 新的名字, 长度不超过 `T::MaxNameLength`
+		#[pallet::weight(0)]
+		pub fn set_name(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			name: Vec<u8>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
 
-			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
+			ensure!(Some(who) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
 
-			let owner1 = Self::owner(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
-			let owner2 = Self::owner(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
+			let bounded_name = Self::bounded_name(&name)?;
+			Self::ensure_name_available(kitty_id, &bounded_name)?;
+			Self::name_kitty(kitty_id, bounded_name, name);
 
-			ensure!(owner1 == who, Error::<T>::NotOwnerOfKitty);
-			ensure!(owner2 == who, Error::<T>::NotOwnerOfKitty);
+			Ok(())
+		}
 
-			let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
-			let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
+		/// 转让账户的所有 Kitties, 用于账户迁移
+		/// 每次最多读取 `T::MaxBatchSize` 个, 事件中的 next 不为 None 时从 next 开始再次调用
+		/// 锁定、质押或在获得后冷却期内的 Kitty 被跳过, 出售中的 Kitty 会被下架
+		/// 任一 Kitty 不能转让时全部不转让
+		/// ### Arguments
+		/// * `origin` - Kitties 的主人
+		/// * `to` - 转让的目标
+		/// * `start` - 上次调用返回的 next, 从它之后继续读取, None 表示从头开始
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn transfer_all(
+			origin: OriginFor<T>,
+			to: T::AccountId,
+			start: Option<T::KittyIndex>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
 
-			let kitty_id = match Self::kitties_count() {
-				Some(id) => {
-					ensure!(id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-					id + 1u32.into()
-				}
-				None => 1u32.into(),
+			let limit = T::MaxBatchSize::get() as usize;
+			let read: Vec<T::KittyIndex> = match start {
+				Some(start) => OwnedKitties::<T>::iter_prefix_from(
+					&sender,
+					OwnedKitties::<T>::hashed_key_for(&sender, start),
+				)
+				.map(|(kitty_id, _)| kitty_id)
+				.take(limit)
+				.collect(),
+				None => OwnedKitties::<T>::iter_key_prefix(&sender).take(limit).collect(),
 			};
+			let next = if read.len() < limit { None } else { read.last().copied() };
 
-			let dna_1 = kitty1.0;
-			let dna_2 = kitty2.0;
+			let (batch, skipped): (Vec<T::KittyIndex>, Vec<T::KittyIndex>) =
+				read.into_iter().partition(|kitty_id| {
+					!Self::is_locked(*kitty_id) &&
+						!Staked::<T>::contains_key(kitty_id) &&
+						Self::ensure_acquire_cooldown_passed(*kitty_id).is_ok()
+				});
 
-			let selector = Self::random_value(&who);
-			let mut new_dna = [0u8; 16];
-
-			for i in 0..dna_1.len() {
-				new_dna[i] = (selector[i] & dna_1[i]) | (!selector[i] & dna_2[i])
+			for kitty_id in batch.iter() {
+				Self::can_transfer(&sender, &to, *kitty_id)?;
+				Self::move_deposit(&sender, &to, *kitty_id)?;
+				Self::cancel_sale(&sender, *kitty_id);
+				Self::transfer_kitty(
+					sender.clone(),
+					to.clone(),
+					*kitty_id,
+					T::EmitTransferEvents::get(),
+				);
 			}
 
-			Kitties::<T>::insert(kitty_id, Some(Kitty(new_dna)));
-			Owner::<T>::insert(kitty_id, Some(&who));
-			KittiesCount::<T>::put(kitty_id);
-
-			Self::deposit_event(Event::KittyCreated(who, kitty_id));
+			Self::deposit_event(Event::KittiesTransferredAll(
+				sender,
+				to,
+				batch.len() as u32,
+				skipped.len() as u32,
+				next,
+			));
 
 			Ok(())
 		}
 
-		/// 出售 Kitty
-		/// price 为 None 时, 表示取消出售
+		/// 转让 Kitty 并设置新的名字, 出售中的 Kitty 会被下架
+		/// 名字不合法时不会转让
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `to` - 转让的目标
+		/// * `kitty_id` - 转让的 Kitty 编号
+		/// * `name` - 新的名字, 长度不超过 `T::MaxNameLength`
+		#[pallet::weight(0)]
+		pub fn transfer_with_name(
+			origin: OriginFor<T>,
+			to: T::AccountId,
+			kitty_id: T::KittyIndex,
+			name: Vec<u8>,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			let bounded_name = Self::bounded_name(&name)?;
+			Self::ensure_name_available(kitty_id, &bounded_name)?;
+			Self::can_transfer(&sender, &to, kitty_id)?;
+
+			Self::move_deposit(&sender, &to, kitty_id)?;
+			Self::cancel_sale(&sender, kitty_id);
+
+			Self::transfer_kitty(sender, to, kitty_id, T::EmitTransferEvents::get());
+			Self::name_kitty(kitty_id, bounded_name, name);
+
+			Ok(())
+		}
+
+		/// 生产 Kitty
+		/// 父母的编号不能相同, `T::BreedingEnabled` 为 false 时不允许生产
+		/// `T::RequireMatchingBreeds` 为 true 时父母必须属于同一品种
+		/// 每个新的 Kitty 需要质押 `T::ReserveOfNewCreate`
+		/// 按 `T::TwinRate` 的概率产下双胞胎, 双胞胎的基因与第一个孩子互补
+		/// 每个区块最多生产 `T::MaxBreedsPerBlock` 次
+		/// ### Arguments
+		/// * `origin` - 生产者
+		/// * `kitty_id_1` - 父亲的编号
+		/// * `kitty_id_2` - 母亲的编号
+		#[pallet::weight(0)]
+		pub fn breed(
+			origin: OriginFor<T>,
+			kitty_id_1: T::KittyIndex,
+			kitty_id_2: T::KittyIndex,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_breed(who, kitty_id_1, kitty_id_2)
+		}
+
+		/// 登记常用的父母, 之后可以直接用 `breed_registered` 生产
+		/// 再次登记时覆盖之前的父母
+		/// ### Arguments
+		/// * `origin` - 父母的主人
+		/// * `kitty_id_1` - 父亲的编号
+		/// * `kitty_id_2` - 母亲的编号
+		#[pallet::weight(0)]
+		pub fn register_pair(
+			origin: OriginFor<T>,
+			kitty_id_1: T::KittyIndex,
+			kitty_id_2: T::KittyIndex,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
+			ensure!(Some(who.clone()) == Self::owner(kitty_id_1), Error::<T>::NotOwnerOfKitty);
+			ensure!(Some(who.clone()) == Self::owner(kitty_id_2), Error::<T>::NotOwnerOfKitty);
+
+			BreedingPairs::<T>::insert(&who, (kitty_id_1, kitty_id_2));
+
+			Ok(())
+		}
+
+		/// 用登记的父母生产 Kitty, 规则与 `breed` 相同
+		/// ### Arguments
+		/// * `origin` - 生产者
+		#[pallet::weight(0)]
+		pub fn breed_registered(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let (kitty_id_1, kitty_id_2) =
+				Self::breeding_pairs(&who).ok_or(Error::<T>::NoBreedingPair)?;
+
+			Self::do_breed(who, kitty_id_1, kitty_id_2)
+		}
+
+		/// 融合 Kitty: 销毁父母, 产下一个孩子
+		/// 父母的质押退还, 孩子质押 `T::ReserveOfNewCreate`
+		/// `T::FusionEnabled` 为 false 时不允许融合
+		/// 父母需要满足生产的条件: 出售中、质押中、休息中、未成年的 Kitty 和传说 Kitty 不能融合
+		/// ### Arguments
+		/// * `origin` - 父母的主人
+		/// * `kitty_id_1` - 父亲的编号
+		/// * `kitty_id_2` - 母亲的编号
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn fuse(
+			origin: OriginFor<T>,
+			kitty_id_1: T::KittyIndex,
+			kitty_id_2: T::KittyIndex,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(T::FusionEnabled::get(), Error::<T>::FusionDisabled);
+			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			for kitty_id in [kitty_id_1, kitty_id_2].iter() {
+				Self::ensure_can_burn(&who, *kitty_id)?;
+				Self::ensure_can_breed(*kitty_id, now)?;
+			}
+
+			let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
+			let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
+
+			let kitty_id = Self::next_kitty_id()?;
+
+			let (_, new_dna) = Self::unique_dna(Self::random_value(&who), |selector| {
+				T::BreedAlgorithm::mix(&kitty1.0, &kitty2.0, &Self::biased_selector(selector))
+			})?;
+			let generation =
+				Self::generation(kitty_id_1).max(Self::generation(kitty_id_2)).saturating_add(1);
+
+			// 先质押孩子, 失败时父母不会被销毁
+			let deposit = Self::creation_deposit()?;
+			if !deposit.is_zero() {
+				Self::ensure_reserve_keeps_alive(&who, deposit)?;
+				T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::NotEnoughBalance)?;
+			}
+
+			Self::burn_kitty(&who, kitty_id_1);
+			Self::burn_kitty(&who, kitty_id_2);
+
+			Self::mint(&who, kitty_id, new_dna, deposit);
+			Parents::<T>::insert(kitty_id, (kitty_id_1, kitty_id_2));
+			Self::set_generation(kitty_id, generation);
+
+			Ok(())
+		}
+
+		/// 出售 Kitty, 以原生货币计价
+		/// price 为 None 时, 表示取消出售
+		/// 已经在出售中的 Kitty 再次出售时, 表示修改价格
+		/// Kitty 被购买后 `T::SaleCooldown` 个区块内不能出售
+		/// 出售价格需要通过 `T::PriceValidator` 的检查
 		/// ### Arguments
 		/// * `origin` - 出售者
 		/// * `kitty_id` - 出售的 Kitty 编号
@@ -228,17 +1389,57 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			kitty_id: T::KittyIndex,
 			price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			Self::sell_in_asset(origin, kitty_id, Zero::zero(), price)
+		}
+
+		/// 以指定的资产出售 Kitty, 资产 0 即原生货币, 与 `sell` 相同
+		/// ### Arguments
+		/// * `origin` - 出售者
+		/// * `kitty_id` - 出售的 Kitty 编号
+		/// * `asset_id` - 计价的资产编号
+		/// * `price` - 出售价格, None 表示取消出售
+		#[pallet::weight(0)]
+		pub fn sell_in_asset(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			asset_id: T::AssetId,
+			price: Option<BalanceOf<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
 			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
 
+			if let Some(price) = price {
+				Self::ensure_not_locked(kitty_id)?;
+				ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+				T::PriceValidator::validate(kitty_id, price)?;
+				Self::ensure_acquire_cooldown_passed(kitty_id)?;
+
+				if let Some(last_sale) = Self::last_sale_block(kitty_id) {
+					ensure!(
+						<frame_system::Pallet<T>>::block_number() >=
+							last_sale.saturating_add(T::SaleCooldown::get()),
+						Error::<T>::SaleCooldownActive
+					);
+				}
+			}
+
+			Self::touch(&who);
+			let old_price = Self::kitties_price(kitty_id);
+			let price = price.map(|price| (asset_id, price));
+
 			KittiesPrice::<T>::mutate_exists(kitty_id, |p| *p = Some(price));
 
-			match price {
-				Some(_) => {
+			match (old_price, price) {
+				(Some(old), Some(new)) => {
+					Self::deposit_event(Event::KittyPriceChanged(who, kitty_id, old, new));
+				}
+				(None, Some(_)) => {
 					Self::deposit_event(Event::KittyForSale(who, kitty_id, price));
 				}
-				None => {
+				(_, None) => {
 					Self::deposit_event(Event::KittyCancelSale(who, kitty_id));
 				}
 			}
@@ -250,58 +1451,1965 @@ pub mod pallet {
 		/// ### Arguments
 		/// * `origin` - 购买者
 		/// * `kitty_id` - 购买的 Kitty 编号
+		/// * `max_price` - 愿意支付的最高价格, None 表示接受任意价格
 		#[pallet::weight(0)]
-		pub fn buy(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+		#[transactional]
+		pub fn buy(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			max_price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
 			let buyer = ensure_signed(origin)?;
+			Self::do_buy(buyer, kitty_id, Zero::zero(), max_price)?;
+			Ok(())
+		}
 
-			let owner = Self::owner(kitty_id).unwrap();
-			ensure!(owner != buyer.clone(), Error::<T>::KittyAlreadyOwned);
-
-			let price = Self::kitties_price(kitty_id).ok_or(Error::<T>::NotForSale)?;
+		/// 购买以指定资产出售的 Kitty, 出售使用的资产不同时返回 `AssetMismatch`
+		/// ### Arguments
+		/// * `origin` - 购买者
+		/// * `kitty_id` - 购买的 Kitty 编号
+		/// * `asset_id` - 支付的资产编号
+		/// * `max_price` - 愿意支付的最高价格, None 表示接受任意价格
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn buy_in_asset(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			asset_id: T::AssetId,
+			max_price: Option<BalanceOf<T>>,
+		) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+			Self::do_buy(buyer, kitty_id, asset_id, max_price)?;
+			Ok(())
+		}
 
-			let reserve = T::ReserveOfNewCreate::get();
+		/// 托管购买 Kitty, 货款转入 Pallet 的账户
+		/// 托管期间 Kitty 被下架并视为锁定, 购买者在 `T::EscrowPeriod` 内确认后成交, 取消后退还货款
+		/// 只支持以原生货币出售的 Kitty, 其他资产返回 `AssetNotSupported`
+		/// ### Arguments
+		/// * `origin` - 购买者
+		/// * `kitty_id` - 购买的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn initiate_buy(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
 
-			// 扣除质押金额
-			T::Currency::reserve(&buyer, reserve).map_err(|_| Error::<T>::NotEnoughBalance)?;
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			ensure!(owner != buyer, Error::<T>::KittyAlreadyOwned);
+			ensure!(!EscrowedPurchases::<T>::contains_key(kitty_id), Error::<T>::EscrowPending);
 
-			// 出售方解除质押
-			T::Currency::unreserve(&owner, reserve);
+			let (asset_id, price) = Self::kitties_price(kitty_id).ok_or(Error::<T>::NotForSale)?;
+			ensure!(asset_id.is_zero(), Error::<T>::AssetNotSupported);
+			Self::ensure_not_locked(kitty_id)?;
+			ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			ensure!(Self::can_receive(&buyer, 1), Error::<T>::RecipientAtCapacity);
+			T::TransferAuthorizer::authorize(&owner, &buyer, kitty_id)?;
 
-			// 转账
 			T::Currency::transfer(
 				&buyer,
+				&Self::account_id(),
+				price,
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			KittiesPrice::<T>::remove(kitty_id);
+			let now = <frame_system::Pallet<T>>::block_number();
+			EscrowedPurchases::<T>::insert(kitty_id, (buyer.clone(), price, now));
+			Self::touch(&buyer);
+
+			Self::deposit_event(Event::PurchaseEscrowed(buyer, kitty_id, price));
+
+			Ok(())
+		}
+
+		/// 确认托管的购买, 托管的货款支付给卖家, Kitty 转移给购买者
+		/// 只能在托管开始后 `T::EscrowPeriod` 个区块内确认
+		/// ### Arguments
+		/// * `origin` - 购买者
+		/// * `kitty_id` - 购买的 Kitty 编号
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn confirm_buy(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			let (escrow_buyer, price, since) =
+				Self::escrowed_purchase(kitty_id).ok_or(Error::<T>::NoEscrow)?;
+			ensure!(escrow_buyer == buyer, Error::<T>::NotEscrowBuyer);
+			ensure!(!Self::escrow_expired(since), Error::<T>::EscrowExpired);
+
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			ensure!(Self::can_receive(&buyer, 1), Error::<T>::RecipientAtCapacity);
+			T::TransferAuthorizer::authorize(&owner, &buyer, kitty_id)?;
+
+			Self::move_deposit(&owner, &buyer, kitty_id)?;
+			T::Currency::transfer(
+				&Self::account_id(),
 				&owner,
 				price,
-				frame_support::traits::ExistenceRequirement::KeepAlive,
+				ExistenceRequirement::AllowDeath,
 			)?;
 
-			// 出售下架
+			EscrowedPurchases::<T>::remove(kitty_id);
+			Self::record_sale(kitty_id, Zero::zero(), price);
+			Self::transfer_kitty(owner, buyer.clone(), kitty_id, true);
+
+			Self::deposit_event(Event::PurchaseConfirmed(buyer, kitty_id));
+
+			Ok(())
+		}
+
+		/// 取消托管的购买, 托管的货款退还给购买者, Kitty 需要重新出售
+		/// 购买者和卖家随时可以取消, 审核期满后任何人都可以取消
+		/// ### Arguments
+		/// * `origin` - 购买者、卖家, 或审核期满后的任何人
+		/// * `kitty_id` - 购买的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn cancel_buy(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			let (buyer, price, since) =
+				Self::escrowed_purchase(kitty_id).ok_or(Error::<T>::NoEscrow)?;
+			ensure!(
+				who == buyer || Self::owner(kitty_id) == Some(who) || Self::escrow_expired(since),
+				Error::<T>::EscrowNotExpired
+			);
+
+			T::Currency::transfer(
+				&Self::account_id(),
+				&buyer,
+				price,
+				ExistenceRequirement::AllowDeath,
+			)?;
+			EscrowedPurchases::<T>::remove(kitty_id);
+
+			Self::deposit_event(Event::PurchaseCancelled(buyer, kitty_id));
+
+			Ok(())
+		}
+
+		/// 锁定 Kitty
+		/// 锁定后不能转让和出售, 直到解除锁定, 出售中的 Kitty 同时下架
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - 锁定的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn lock(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+
+			Self::clear_lock_expiry(kitty_id);
+			LockedKitties::<T>::insert(kitty_id, None::<T::BlockNumber>);
+			Self::touch(&who);
+			Self::cancel_sale(&who, kitty_id);
+
+			Self::deposit_event(Event::KittyLockChanged(kitty_id, true));
+
+			Ok(())
+		}
+
+		/// 锁定 Kitty 到指定区块
+		/// 到达 `until` 区块后自动解除锁定, 出售中的 Kitty 同时下架
+		/// 同一区块到期的锁定超过 `T::MaxLockExpiriesPerBlock` 时失败
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - 锁定的 Kitty 编号
+		/// * `until` - 解除锁定的区块
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn lock_until(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			until: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+			ensure!(
+				until > <frame_system::Pallet<T>>::block_number(),
+				Error::<T>::InvalidLockExpiry
+			);
+
+			Self::clear_lock_expiry(kitty_id);
+			LockExpiries::<T>::try_mutate(until, |expiries| expiries.try_push(kitty_id))
+				.map_err(|_| Error::<T>::TooManyLockExpiries)?;
+			LockedKitties::<T>::insert(kitty_id, Some(until));
+			Self::touch(&who);
+			Self::cancel_sale(&who, kitty_id);
+
+			Self::deposit_event(Event::KittyLockChanged(kitty_id, true));
+
+			Ok(())
+		}
+
+		/// 解除锁定
+		/// 没有锁定时不发出事件
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - 解除锁定的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn unlock(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+
+			let was_locked = LockedKitties::<T>::contains_key(kitty_id);
+			Self::clear_lock_expiry(kitty_id);
+			LockedKitties::<T>::remove(kitty_id);
+			Self::touch(&who);
+
+			if was_locked {
+				Self::deposit_event(Event::KittyLockChanged(kitty_id, false));
+			}
+
+			Ok(())
+		}
+
+		/// 质押 Kitty, 质押中的 Kitty 不能转让, 出售或销毁
+		/// 出售中的 Kitty 同时下架
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - 质押的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn stake(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+			ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			// 托管购买中的 Kitty 视为锁定, 不能质押
+			Self::ensure_not_locked(kitty_id)?;
+
+			Staked::<T>::insert(kitty_id, <frame_system::Pallet<T>>::block_number());
+			Self::touch(&who);
+			Self::cancel_sale(&who, kitty_id);
+
+			Self::deposit_event(Event::KittyStaked(who, kitty_id));
+
+			Ok(())
+		}
+
+		/// 解除质押
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - 解除质押的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn unstake(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+			ensure!(Staked::<T>::contains_key(kitty_id), Error::<T>::NotStaked);
+
+			Staked::<T>::remove(kitty_id);
+			Self::touch(&who);
+
+			Self::deposit_event(Event::KittyUnstaked(who, kitty_id));
+
+			Ok(())
+		}
+
+		/// 认领被遗弃的 Kitty
+		/// 主人超过 `T::AbandonmentPeriod` 个区块没有操作时, 其他人可以认领
+		/// 认领者支付 `T::AbandonmentClaimFee` 给 Pallet 的账户
+		/// 没有操作记录的主人不会被视为遗弃, 锁定的 Kitty 不能认领
+		/// ### Arguments
+		/// * `origin` - 认领者
+		/// * `kitty_id` - 认领的 Kitty 编号
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn claim_abandoned(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let claimer = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+
+			let last_activity = Self::last_activity(&owner).ok_or(Error::<T>::NotAbandoned)?;
+			ensure!(
+				<frame_system::Pallet<T>>::block_number() >=
+					last_activity.saturating_add(T::AbandonmentPeriod::get()),
+				Error::<T>::NotAbandoned
+			);
+
+			Self::can_transfer(&owner, &claimer, kitty_id)?;
+
+			T::Currency::transfer(
+				&claimer,
+				&Self::account_id(),
+				T::AbandonmentClaimFee::get(),
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			Self::move_deposit(&owner, &claimer, kitty_id)?;
+
 			KittiesPrice::<T>::remove(kitty_id);
+			Self::transfer_kitty(owner, claimer.clone(), kitty_id, true);
 
-			Self::transfer_kitty(owner, buyer, kitty_id);
+			Self::deposit_event(Event::KittyClaimed(claimer, kitty_id));
 
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
-		/// 随机数生成
+		/// 销毁 Kitty
+		/// 退还创建时的质押金额, 出售中的 Kitty 同时下架
 		/// ### Arguments
-		/// * `who` - 生成随机数的人
-		fn random_value(who: &T::AccountId) -> [u8; 16] {
-			let payload =
-				(T::Randomness::random_seed(), &who, <frame_system::Pallet<T>>::extrinsic_index());
-			payload.using_encoded(blake2_128)
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - 销毁的 Kitty 编号
+		#[pallet::weight(0)]
+		pub fn burn(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			Self::ensure_can_burn(&who, kitty_id)?;
+			Self::burn_kitty(&who, kitty_id);
+
+			Ok(())
 		}
 
-		/// 转移 Kitty
+		/// 批量销毁 Kitties
+		/// 销毁前校验所有 Kitty, 任一 Kitty 不能销毁时全部不销毁
 		/// ### Arguments
-		/// * `owner` - 原来的主人
-		/// * `new_owner` - 新的主人
-		/// * `kitty_id` - 转移的 Kitty 编号
-		fn transfer_kitty(from: T::AccountId, to: T::AccountId, kitty_id: T::KittyIndex) {
-			Owner::<T>::insert(kitty_id, Some(to.clone()));
-			Self::deposit_event(Event::KittyTransfered(from, to, kitty_id));
+		/// * `origin` - Kitties 的主人
+		/// * `kitty_ids` - 销毁的 Kitty 编号, 数量不能超过 `T::MaxBatchSize`
+		#[pallet::weight(0)]
+		pub fn burn_many(origin: OriginFor<T>, kitty_ids: Vec<T::KittyIndex>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(
+				kitty_ids.len() <= T::MaxBatchSize::get() as usize,
+				Error::<T>::TooManyKitties
+			);
+
+			let mut unique_ids = kitty_ids.clone();
+			unique_ids.sort();
+			unique_ids.dedup();
+			ensure!(unique_ids.len() == kitty_ids.len(), Error::<T>::DuplicateKittyIndex);
+
+			for kitty_id in kitty_ids.iter() {
+				Self::ensure_can_burn(&who, *kitty_id)?;
+			}
+
+			for kitty_id in kitty_ids {
+				Self::burn_kitty(&who, kitty_id);
+			}
+
+			Ok(())
+		}
+
+		/// 重置 Kitties 总数
+		/// 用于存储不一致时的修复, 新的值不能小于当前值, 避免编号被重复使用
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `value` - 新的 Kitties 总数
+		#[pallet::weight(0)]
+		pub fn set_kitties_count(origin: OriginFor<T>, value: T::KittyIndex) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			if let Some(current) = Self::kitties_count() {
+				ensure!(value >= current, Error::<T>::KittiesCountTooLow);
+			}
+
+			KittiesCount::<T>::put(value);
+			Self::check_count_warning(value);
+
+			Self::deposit_event(Event::KittiesCountReset(value));
+
+			Ok(())
+		}
+
+		/// 解除账户的质押, 用于修复没有对应 Kitty 的质押
+		/// 质押不足 `amount` 时解除全部质押, 事件中为实际解除的金额
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `who` - 账户
+		/// * `amount` - 解除质押的金额
+		#[pallet::weight(0)]
+		pub fn force_unreserve(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let remaining = T::Currency::unreserve(&who, amount);
+
+			Self::deposit_event(Event::DepositUnreserved(who, amount.saturating_sub(remaining)));
+
+			Ok(())
+		}
+
+		/// 创建 Cattery, 用于把自己的 Kitties 分组
+		/// 需要质押 `T::CatteryDeposit`, 删除 Cattery 时退还
+		/// ### Arguments
+		/// * `origin` - Cattery 的主人
+		/// * `name` - Cattery 的名字, 长度不超过 `T::MaxNameLength`
+		#[pallet::weight(0)]
+		pub fn create_cattery(origin: OriginFor<T>, name: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let name = Self::bounded_name(&name)?;
+			let cattery_id = Self::next_cattery_id();
+			let next_id = cattery_id.checked_add(1).ok_or(Error::<T>::CatteriesCountOverflow)?;
+
+			let deposit = T::CatteryDeposit::get();
+			T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::NotEnoughBalance)?;
+			CatteryDeposits::<T>::insert(cattery_id, deposit);
+			Catteries::<T>::insert(cattery_id, (who.clone(), name, BoundedVec::default()));
+			NextCatteryId::<T>::put(next_id);
+
+			Self::deposit_event(Event::CatteryCreated(who, cattery_id));
+
+			Ok(())
+		}
+
+		/// 把自己的 Kitty 加入 Cattery
+		/// 已经在其他 Cattery 中的 Kitty 需要先移出
+		/// ### Arguments
+		/// * `origin` - Cattery 和 Kitty 的主人
+		/// * `cattery_id` - Cattery 编号
+		/// * `kitty_id` - Kitty 编号
+		#[pallet::weight(0)]
+		pub fn add_to_cattery(
+			origin: OriginFor<T>,
+			cattery_id: u32,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+
+			Catteries::<T>::try_mutate(cattery_id, |cattery| -> DispatchResult {
+				let (owner, _, kitties) = cattery.as_mut().ok_or(Error::<T>::CatteryNotFound)?;
+				ensure!(*owner == who, Error::<T>::NotCatteryOwner);
+				ensure!(!KittyCattery::<T>::contains_key(kitty_id), Error::<T>::AlreadyInCattery);
+				kitties.try_push(kitty_id).map_err(|_| Error::<T>::CatteryFull)?;
+				Ok(())
+			})?;
+			KittyCattery::<T>::insert(kitty_id, cattery_id);
+
+			Self::deposit_event(Event::KittyAddedToCattery(cattery_id, kitty_id));
+
+			Ok(())
+		}
+
+		/// 把 Kitty 移出 Cattery
+		/// 转让或销毁的 Kitty 会自动移出 Cattery
+		/// ### Arguments
+		/// * `origin` - Cattery 的主人
+		/// * `cattery_id` - Cattery 编号
+		/// * `kitty_id` - Kitty 编号
+		#[pallet::weight(0)]
+		pub fn remove_from_cattery(
+			origin: OriginFor<T>,
+			cattery_id: u32,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Catteries::<T>::try_mutate(cattery_id, |cattery| -> DispatchResult {
+				let (owner, _, kitties) = cattery.as_mut().ok_or(Error::<T>::CatteryNotFound)?;
+				ensure!(*owner == who, Error::<T>::NotCatteryOwner);
+				let index = kitties
+					.iter()
+					.position(|id| *id == kitty_id)
+					.ok_or(Error::<T>::NotInCattery)?;
+				kitties.remove(index);
+				Ok(())
+			})?;
+			KittyCattery::<T>::remove(kitty_id);
+
+			Self::deposit_event(Event::KittyRemovedFromCattery(cattery_id, kitty_id));
+
+			Ok(())
+		}
+
+		/// 删除 Cattery, 其中的 Kitties 全部移出, 退还创建时的质押
+		/// ### Arguments
+		/// * `origin` - Cattery 的主人
+		/// * `cattery_id` - Cattery 编号
+		#[pallet::weight(0)]
+		pub fn remove_cattery(origin: OriginFor<T>, cattery_id: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			let (owner, _, kitties) =
+				Self::catteries(cattery_id).ok_or(Error::<T>::CatteryNotFound)?;
+			ensure!(owner == who, Error::<T>::NotCatteryOwner);
+
+			for kitty_id in kitties {
+				KittyCattery::<T>::remove(kitty_id);
+			}
+			Catteries::<T>::remove(cattery_id);
+			T::Currency::unreserve(&who, CatteryDeposits::<T>::take(cattery_id));
+
+			Self::deposit_event(Event::CatteryRemoved(who, cattery_id));
+
+			Ok(())
+		}
+
+		/// 记录 Kitties 新增的浏览次数
+		/// 记录前校验所有 Kitty, 任一 Kitty 不存在时全部不记录
+		/// ### Arguments
+		/// * `origin` - 预言机
+		/// * `updates` - Kitty 编号和新增的浏览次数, 数量不能超过 `T::MaxBatchSize`
+		#[pallet::weight(0)]
+		pub fn record_views(
+			origin: OriginFor<T>,
+			updates: Vec<(T::KittyIndex, u64)>,
+		) -> DispatchResult {
+			T::ViewOracle::ensure_origin(origin)?;
+
+			ensure!(updates.len() <= T::MaxBatchSize::get() as usize, Error::<T>::TooManyKitties);
+			for (kitty_id, _) in updates.iter() {
+				ensure!(Owner::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
+			}
+
+			for (kitty_id, views) in updates.iter() {
+				ViewCounts::<T>::mutate(kitty_id, |count| *count = count.saturating_add(*views));
+			}
+
+			Self::deposit_event(Event::ViewsRecorded(updates.len() as u32));
+
+			Ok(())
+		}
+
+		/// 提取 Pallet 账户中累积的手续费
+		/// 托管中的货款属于购买者, 不能提取
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `to` - 收款账户
+		/// * `amount` - 提取的金额
+		#[pallet::weight(0)]
+		pub fn withdraw_fees(
+			origin: OriginFor<T>,
+			to: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			ensure!(amount <= Self::withdrawable_fees(), Error::<T>::InsufficientTreasuryBalance);
+			T::Currency::transfer(
+				&Self::account_id(),
+				&to,
+				amount,
+				ExistenceRequirement::AllowDeath,
+			)?;
+
+			Self::deposit_event(Event::FeesWithdrawn(to, amount));
+
+			Ok(())
+		}
+
+		/// 由 `Owner` 重建账户的 `OwnedKitties` 和 `OwnedKittiesCount`
+		/// 需要扫描所有 Kitty, 数量超过 `max_scan` 时不做修改
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `account` - 重建索引的账户
+		/// * `max_scan` - 最多扫描的 Kitty 数量
+		#[pallet::weight(0)]
+		pub fn repair_owned_list(
+			origin: OriginFor<T>,
+			account: T::AccountId,
+			max_scan: u32,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let mut scanned: u32 = 0;
+			let mut owned = Vec::new();
+			for (kitty_id, owner) in Owner::<T>::iter() {
+				scanned += 1;
+				ensure!(scanned <= max_scan, Error::<T>::RepairScanExceeded);
+				if owner == account {
+					owned.push(kitty_id);
+				}
+			}
+
+			OwnedKitties::<T>::drain_prefix(&account).for_each(drop);
+			for kitty_id in owned.iter() {
+				OwnedKitties::<T>::insert(&account, kitty_id, ());
+			}
+
+			let count = owned.len() as u32;
+			OwnedKittiesCount::<T>::insert(&account, count);
+
+			Self::deposit_event(Event::OwnedListRepaired(account, count));
+
+			Ok(())
+		}
+
+		/// 允许账户创建 Kitty
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `who` - 账户
+		#[pallet::weight(0)]
+		pub fn add_minter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			Minters::<T>::insert(&who, true);
+
+			Self::deposit_event(Event::MinterAdded(who));
+
+			Ok(())
+		}
+
+		/// 禁止账户创建 Kitty
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `who` - 账户
+		#[pallet::weight(0)]
+		pub fn remove_minter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			Minters::<T>::remove(&who);
+
+			Self::deposit_event(Event::MinterRemoved(who));
+
+			Ok(())
+		}
+
+		/// 设置 Kitty 的品种
+		/// ### Arguments
+		/// * `origin` - 管理员
+		/// * `kitty_id` - Kitty 编号
+		/// * `tag` - 品种
+		#[pallet::weight(0)]
+		pub fn set_breed_tag(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			tag: u8,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			ensure!(Owner::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
+
+			BreedTag::<T>::insert(kitty_id, tag);
+
+			Self::deposit_event(Event::BreedTagSet(kitty_id, tag));
+
+			Ok(())
+		}
+
+		/// 设置 Kitty 的属性
+		/// 新增属性时需要质押 `T::AttributeDeposit`, 覆盖已有属性时不再质押
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		/// * `key` - 属性名
+		/// * `value` - 属性值
+		#[pallet::weight(0)]
+		pub fn set_attribute(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			key: Vec<u8>,
+			value: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_not_reentrant()?;
+
+			ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+
+			let bounded_key = BoundedVec::<u8, T::MaxKeyLen>::try_from(key.clone())
+				.map_err(|_| Error::<T>::AttributeKeyTooLong)?;
+			let bounded_value = BoundedVec::<u8, T::MaxValueLen>::try_from(value.clone())
+				.map_err(|_| Error::<T>::AttributeValueTooLong)?;
+
+			if !Attributes::<T>::contains_key(kitty_id, &bounded_key) {
+				let deposit = T::AttributeDeposit::get();
+				T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::NotEnoughBalance)?;
+				AttributeDeposits::<T>::insert(kitty_id, &bounded_key, (who, deposit));
+			}
+
+			Attributes::<T>::insert(kitty_id, &bounded_key, bounded_value);
+
+			Self::deposit_event(Event::AttributeSet(kitty_id, key, value));
+
+			Ok(())
+		}
+
+		/// 清除 Kitty 的属性
+		/// 质押退还给设置属性的账户
+		/// ### Arguments
+		/// * `origin` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		/// * `key` - 属性名
+		#[pallet::weight(0)]
+		pub fn clear_attribute(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			key: Vec<u8>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Some(who) == Self::owner(kitty_id), Error::<T>::NotOwnerOfKitty);
+
+			let bounded_key = BoundedVec::<u8, T::MaxKeyLen>::try_from(key.clone())
+				.map_err(|_| Error::<T>::AttributeKeyTooLong)?;
+
+			ensure!(
+				Attributes::<T>::contains_key(kitty_id, &bounded_key),
+				Error::<T>::AttributeNotFound
+			);
+
+			Attributes::<T>::remove(kitty_id, &bounded_key);
+			if let Some((depositor, deposit)) = AttributeDeposits::<T>::take(kitty_id, &bounded_key)
+			{
+				T::Currency::unreserve(&depositor, deposit);
+			}
+
+			Self::deposit_event(Event::AttributeCleared(kitty_id, key));
+
+			Ok(())
+		}
+
+		/// 发出索引快照标记
+		/// 不修改存储, 通知链下索引服务重新扫描
+		/// ### Arguments
+		/// * `origin` - 管理员
+		#[pallet::weight(10_000)]
+		pub fn emit_snapshot_marker(origin: OriginFor<T>) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let block_number = <frame_system::Pallet<T>>::block_number();
+			Self::deposit_event(Event::SnapshotMarker(block_number));
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// 生产 Kitty, 检查父母后铸造新的 Kitty
+		/// ### Arguments
+		/// * `who` - 生产者
+		/// * `kitty_id_1` - 父亲的编号
+		/// * `kitty_id_2` - 母亲的编号
+		#[transactional]
+		fn do_breed(
+			who: T::AccountId,
+			kitty_id_1: T::KittyIndex,
+			kitty_id_2: T::KittyIndex,
+		) -> DispatchResult {
+			Self::ensure_not_reentrant()?;
+
+			ensure!(T::BreedingEnabled::get(), Error::<T>::BreedingDisabled);
+			ensure!(
+				Self::breeds_this_block() < T::MaxBreedsPerBlock::get(),
+				Error::<T>::BreedLimitReached
+			);
+			let now = <frame_system::Pallet<T>>::block_number();
+			let (last_block, account_breeds) = Self::account_breeds(&who);
+			let account_breeds = if last_block == now { account_breeds } else { 0 };
+			ensure!(
+				account_breeds < T::MaxBreedsPerAccountPerBlock::get(),
+				Error::<T>::BreedLimitReached
+			);
+			ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
+
+			let owner1 = Self::owner(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
+			let owner2 = Self::owner(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
+
+			ensure!(owner1 == who, Error::<T>::NotOwnerOfKitty);
+			ensure!(owner2 == who, Error::<T>::NotOwnerOfKitty);
+
+			for kitty_id in [kitty_id_1, kitty_id_2].iter() {
+				Self::ensure_not_locked(*kitty_id)?;
+				Self::ensure_can_breed(*kitty_id, now)?;
+			}
+
+			let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
+			let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
+
+			// 父母品种相同时孩子继承品种, 没有品种的父母不属于任何品种
+			let tag_1 = Self::breed_tag(kitty_id_1);
+			let same_breed = tag_1.is_some() && tag_1 == Self::breed_tag(kitty_id_2);
+			ensure!(
+				same_breed || !T::RequireMatchingBreeds::get(),
+				Error::<T>::IncompatibleBreeds
+			);
+			let child_tag = if same_breed { tag_1 } else { None };
+
+			ensure!(Self::can_receive(&who, 1), Error::<T>::RecipientAtCapacity);
+
+			let generation =
+				Self::generation(kitty_id_1).max(Self::generation(kitty_id_2)).saturating_add(1);
+
+			let kitty_id = Self::next_kitty_id()?;
+
+			let (selector, new_dna) = Self::unique_dna(Self::random_value(&who), |selector| {
+				let dna =
+					T::BreedAlgorithm::mix(&kitty1.0, &kitty2.0, &Self::biased_selector(selector));
+				Self::mutate_trait(dna, selector)
+			})?;
+
+			// 双胞胎的 DNA 重复, 超过拥有上限或编号已用尽时只产下一个孩子
+			let twin = if Self::is_twin_birth(&selector) &&
+				Self::can_receive(&who, 2) &&
+				kitty_id != T::KittyIndex::max_value()
+			{
+				let twin_selector: Vec<u8> =
+					Self::biased_selector(&selector).iter().map(|byte| !byte).collect();
+				let twin_dna = Self::mutate_trait(
+					T::BreedAlgorithm::mix(&kitty1.0, &kitty2.0, &twin_selector),
+					&twin_selector,
+				);
+
+				if twin_dna != new_dna && !DnaIndex::<T>::contains_key(Self::dna_hash(&twin_dna)) {
+					Some((kitty_id + 1u32.into(), twin_dna))
+				} else {
+					None
+				}
+			} else {
+				None
+			};
+
+			// 扣除质押金额, 双胞胎需要两份
+			let deposit = Self::creation_deposit()?;
+			let total_deposit =
+				if twin.is_some() { deposit.saturating_add(deposit) } else { deposit };
+			let fee = Self::breed_fee(kitty_id_1, kitty_id_2);
+			ensure!(
+				T::Currency::can_reserve(&who, total_deposit.saturating_add(fee)),
+				Error::<T>::NotEnoughBalance
+			);
+			if !total_deposit.is_zero() {
+				Self::ensure_reserve_keeps_alive(&who, total_deposit.saturating_add(fee))?;
+			}
+			Self::ensure_deposit_cap(&who, total_deposit)?;
+
+			// 先检查余额, 避免道具消耗后质押失败
+			T::BreedConsumable::consume(&who)?;
+
+			if !fee.is_zero() {
+				T::Currency::transfer(
+					&who,
+					&Self::account_id(),
+					fee,
+					ExistenceRequirement::KeepAlive,
+				)?;
+			}
+
+			if !total_deposit.is_zero() {
+				T::Currency::reserve(&who, total_deposit)
+					.map_err(|_| Error::<T>::NotEnoughBalance)?;
+			}
+
+			Self::mint(&who, kitty_id, new_dna, deposit);
+			Parents::<T>::insert(kitty_id, (kitty_id_1, kitty_id_2));
+			Self::set_generation(kitty_id, generation);
+			if let Some(tag) = child_tag {
+				BreedTag::<T>::insert(kitty_id, tag);
+			}
+
+			if let Some((twin_id, twin_dna)) = twin {
+				Self::mint(&who, twin_id, twin_dna, deposit);
+				Parents::<T>::insert(twin_id, (kitty_id_1, kitty_id_2));
+				Self::set_generation(twin_id, generation);
+				if let Some(tag) = child_tag {
+					BreedTag::<T>::insert(twin_id, tag);
+				}
+			}
+
+			Self::start_breed_cooldown(kitty_id_1, now);
+			Self::start_breed_cooldown(kitty_id_2, now);
+			BreedCount::<T>::mutate(kitty_id_1, |count| *count = count.saturating_add(1));
+			BreedCount::<T>::mutate(kitty_id_2, |count| *count = count.saturating_add(1));
+			Self::pay_breed_reward(&who);
+
+			BreedsThisBlock::<T>::mutate(|count| *count = count.saturating_add(1));
+			AccountBreeds::<T>::insert(&who, (now, account_breeds.saturating_add(1)));
+
+			Ok(())
+		}
+
+		/// 检查 Kitty 可以作为父母: 没有出售, 不是不能生产的传说 Kitty, 已过休息期且已成年
+		/// 生产和融合共用, 锁定和所有权由调用者检查
+		/// ### Arguments
+		/// * `kitty_id` - 父母的编号
+		/// * `now` - 当前区块
+		fn ensure_can_breed(kitty_id: T::KittyIndex, now: T::BlockNumber) -> DispatchResult {
+			ensure!(
+				T::AllowBreedingListed::get() || Self::kitties_price(kitty_id).is_none(),
+				Error::<T>::ParentListedForSale
+			);
+			let legendary = Self::is_legendary(kitty_id);
+			ensure!(
+				!legendary || T::LegendaryBypassCooldown::get(),
+				Error::<T>::LegendaryCannotBreed
+			);
+			if let Some(until) = Self::breed_cooldown_until(kitty_id) {
+				ensure!(legendary || now >= until, Error::<T>::BreedCooldownActive);
+			}
+			// 没有出生区块记录的 Kitty 视为已成年
+			if let Some(age) = Self::kitty_age(kitty_id) {
+				ensure!(age >= T::MinBreedAge::get(), Error::<T>::KittyTooYoung);
+			}
+
+			Ok(())
+		}
+
+		/// 父母生产一次需要的手续费
+		/// 父母中生产次数较多的一个每生产过一次, 手续费增加 `T::BreedCostScaling` 倍的 `T::BreedFee`
+		/// ### Arguments
+		/// * `kitty_id_1` - 父亲的编号
+		/// * `kitty_id_2` - 母亲的编号
+		pub fn breed_fee(kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> BalanceOf<T> {
+			let base = T::BreedFee::get();
+			let count = Self::breed_count(kitty_id_1).max(Self::breed_count(kitty_id_2));
+			let step = T::BreedCostScaling::get() * base;
+			base.saturating_add(step.saturating_mul(count.into()))
+		}
+
+		/// 生产后开始休息, 代数越高休息越久
+		/// ### Arguments
+		/// * `kitty_id` - 父母的编号
+		/// * `now` - 当前区块
+		fn start_breed_cooldown(kitty_id: T::KittyIndex, now: T::BlockNumber) {
+			let base = T::BaseBreedCooldown::get();
+			if base.is_zero() || Self::is_legendary(kitty_id) {
+				return
+			}
+
+			let factor = Self::generation(kitty_id).saturating_add(1);
+			let until = now.saturating_add(base.saturating_mul(factor.into()));
+			BreedCooldownUntil::<T>::insert(kitty_id, until);
+		}
+
+		/// 铸造生产奖励给生产者, 接近上限时只奖励剩余的部分
+		/// 奖励已达到上限时跳过铸造, 不影响生产
+		/// ### Arguments
+		/// * `who` - 生产者
+		fn pay_breed_reward(who: &T::AccountId) {
+			let paid = Self::total_breed_rewards();
+			let reward = T::BreedReward::get().min(T::MaxBreedRewards::get().saturating_sub(paid));
+			if reward.is_zero() {
+				return
+			}
+
+			drop(T::Currency::deposit_creating(who, reward));
+			TotalBreedRewards::<T>::put(paid.saturating_add(reward));
+
+			Self::deposit_event(Event::BreedRewardPaid(who.clone(), reward));
+		}
+
+		/// 导出 Kitty 并销毁本地的 Kitty, 用于跨链转移
+		/// 锁定的 Kitty 不能导出
+		/// ### Arguments
+		/// * `kitty_id` - 导出的 Kitty 编号
+		pub fn export_kitty(
+			kitty_id: T::KittyIndex,
+		) -> Result<ExportedKitty<T::KittyIndex>, DispatchError> {
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			let kitty = Self::kitties(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			Self::ensure_not_locked(kitty_id)?;
+			ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+
+			let exported = ExportedKitty {
+				dna: kitty.0,
+				generation: Self::generation(kitty_id),
+				parents: Self::parents(kitty_id),
+			};
+
+			Self::burn_kitty(&owner, kitty_id);
+
+			Ok(exported)
+		}
+
+		/// 由导出的数据重建 Kitty, 主人需要质押 `T::ReserveOfNewCreate`
+		/// 返回新的 Kitty 编号
+		/// ### Arguments
+		/// * `data` - 导出的 Kitty
+		/// * `owner` - 新的主人
+		pub fn import_kitty(
+			data: ExportedKitty<T::KittyIndex>,
+			owner: T::AccountId,
+		) -> Result<T::KittyIndex, DispatchError> {
+			Self::ensure_not_reentrant()?;
+
+			let kitty_id = Self::next_kitty_id()?;
+
+			ensure!(
+				data.dna.len() == T::DnaLength::get() as usize,
+				Error::<T>::InvalidDnaLength
+			);
+			ensure!(
+				!DnaIndex::<T>::contains_key(Self::dna_hash(&data.dna)),
+				Error::<T>::DuplicateDna
+			);
+			ensure!(Self::can_receive(&owner, 1), Error::<T>::RecipientAtCapacity);
+
+			let deposit = Self::creation_deposit()?;
+			Self::ensure_deposit_cap(&owner, deposit)?;
+			if !deposit.is_zero() {
+				Self::ensure_reserve_keeps_alive(&owner, deposit)?;
+				T::Currency::reserve(&owner, deposit).map_err(|_| Error::<T>::NotEnoughBalance)?;
+			}
+
+			Self::mint(&owner, kitty_id, data.dna, deposit);
+			if let Some(parents) = data.parents {
+				Parents::<T>::insert(kitty_id, parents);
+			}
+			if !data.generation.is_zero() {
+				Self::set_generation(kitty_id, data.generation);
+			}
+
+			Ok(kitty_id)
+		}
+
+		/// 预览生产 Kitty 的 DNA, 不写入存储, 包括特征突变
+		/// 任一父母不存在时返回 None
+		/// ### Arguments
+		/// * `kitty_id_1` - 父亲的编号
+		/// * `kitty_id_2` - 母亲的编号
+		/// * `selector` - 选择父母基因的随机数, 按 `T::InheritanceBias` 转换后使用
+		pub fn breed_preview(
+			kitty_id_1: T::KittyIndex,
+			kitty_id_2: T::KittyIndex,
+			selector: Vec<u8>,
+		) -> Option<Vec<u8>> {
+			let kitty1 = Self::kitties(kitty_id_1)?;
+			let kitty2 = Self::kitties(kitty_id_2)?;
+			let dna =
+				T::BreedAlgorithm::mix(&kitty1.0, &kitty2.0, &Self::biased_selector(&selector));
+			Some(Self::mutate_trait(dna, &selector))
+		}
+
+		/// 按 `T::InheritanceBias` 把随机数转换为选择父母基因的 selector
+		/// 字节小于 bias 时为 0xFF, 选择父亲; 否则为 0x00, 选择母亲
+		/// ### Arguments
+		/// * `random` - 随机数
+		fn biased_selector(random: &[u8]) -> Vec<u8> {
+			let bias = T::InheritanceBias::get();
+			random.iter().map(|byte| if *byte < bias { 0xFF } else { 0x00 }).collect()
+		}
+
+		/// 以原生货币出售的 Kitty 中价格最低的一个
+		/// 没有 Kitty 以原生货币出售时返回 None
+		pub fn floor_price() -> Option<(T::KittyIndex, BalanceOf<T>)> {
+			KittiesPrice::<T>::iter()
+				.filter_map(|(kitty_id, price)| match price {
+					Some((asset_id, price)) if asset_id.is_zero() => Some((kitty_id, price)),
+					_ => None,
+				})
+				.min_by_key(|(_, price)| *price)
+		}
+
+		/// 账户拥有的 Kitty 编号
+		/// ### Arguments
+		/// * `owner` - 账户
+		pub fn kitties_of(owner: &T::AccountId) -> Vec<T::KittyIndex> {
+			OwnedKitties::<T>::iter_key_prefix(owner).collect()
+		}
+
+		/// 账户以原生货币出售中的 Kitty 的总价
+		/// ### Arguments
+		/// * `owner` - 账户
+		pub fn portfolio_listed_value(owner: &T::AccountId) -> BalanceOf<T> {
+			OwnedKitties::<T>::iter_key_prefix(owner)
+				.filter_map(|kitty_id| Self::kitties_price(kitty_id))
+				.filter(|(asset_id, _)| asset_id.is_zero())
+				.map(|(_, price)| price)
+				.fold(Zero::zero(), |total: BalanceOf<T>, price| total.saturating_add(price))
+		}
+
+		/// 批量查询 Kitty 的信息, 结果与编号的顺序相同, 不存在的 Kitty 为 None
+		/// 最多查询 `T::MaxBatchSize` 个, 超出的编号被忽略
+		/// ### Arguments
+		/// * `kitty_ids` - Kitty 编号
+		pub fn kitties_info(
+			kitty_ids: Vec<T::KittyIndex>,
+		) -> Vec<Option<KittyInfo<T::AccountId, T::AssetId, BalanceOf<T>>>> {
+			kitty_ids
+				.into_iter()
+				.take(T::MaxBatchSize::get() as usize)
+				.map(|kitty_id| {
+					let kitty = Self::kitties(kitty_id)?;
+					let owner = Self::owner(kitty_id)?;
+					Some(KittyInfo {
+						dna: kitty.0,
+						owner,
+						price: Self::kitties_price(kitty_id),
+						generation: Self::generation(kitty_id),
+					})
+				})
+				.collect()
+		}
+
+		/// Kitty 的完整公开记录, Kitty 不存在时返回 None
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn kitty_record(
+			kitty_id: T::KittyIndex,
+		) -> Option<
+			KittyRecord<T::AccountId, T::AssetId, BalanceOf<T>, T::BlockNumber, T::KittyIndex>,
+		> {
+			let kitty = Self::kitties(kitty_id)?;
+			let owner = Self::owner(kitty_id)?;
+			Some(KittyRecord {
+				dna: kitty.0,
+				owner,
+				generation: Self::generation(kitty_id),
+				parents: Self::parents(kitty_id),
+				price: Self::kitties_price(kitty_id),
+				name: Self::kitty_name(kitty_id).map(Into::into),
+				locked: Self::is_locked(kitty_id),
+				created_at: Self::birth_block(kitty_id),
+			})
+		}
+
+		/// 分配下一个 Kitty 编号, 编号已用尽时返回 `KittiesCountOverflow`
+		pub fn next_kitty_id() -> Result<T::KittyIndex, DispatchError> {
+			match Self::kitties_count() {
+				Some(id) => {
+					ensure!(id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
+					Ok(id + 1u32.into())
+				}
+				None => Ok(1u32.into()),
+			}
+		}
+
+		/// 下一个创建或生产的 Kitty 将使用的编号
+		/// 编号已用尽时返回最大值, 此时创建会失败
+		pub fn next_index() -> T::KittyIndex {
+			match Self::kitties_count() {
+				Some(id) => id.saturating_add(1u32.into()),
+				None => 1u32.into(),
+			}
+		}
+
+		/// Pallet 账户中可提取的手续费, 即余额减去托管中的货款
+		/// 会遍历所有托管中的购买
+		pub fn withdrawable_fees() -> BalanceOf<T> {
+			let escrowed = EscrowedPurchases::<T>::iter_values()
+				.fold(Zero::zero(), |total: BalanceOf<T>, (_, price, _)| {
+					total.saturating_add(price)
+				});
+			T::Currency::free_balance(&Self::account_id()).saturating_sub(escrowed)
+		}
+
+		/// 市场累计的成交次数和成交金额, 成交金额只统计原生货币
+		pub fn market_stats() -> (u32, BalanceOf<T>) {
+			(Self::total_sales_count(), Self::total_volume())
+		}
+
+		/// 市场参数, 由配置读取
+		pub fn market_params() -> MarketParams<BalanceOf<T>, T::BlockNumber> {
+			MarketParams {
+				decimals: T::BalanceDecimals::get(),
+				reserve: if T::RequireDeposit::get() {
+					T::ReserveOfNewCreate::get()
+				} else {
+					Zero::zero()
+				},
+				attribute_deposit: T::AttributeDeposit::get(),
+				claim_fee: T::AbandonmentClaimFee::get(),
+				sale_cooldown: T::SaleCooldown::get(),
+			}
+		}
+
+		/// 两个 Kitty 的契合度, 0 - 100
+		/// 按 DNA 中不同的位所占的比例计算: DNA 相同时为 0, 完全互补时为 100
+		/// DNA 长度不同时只比较较短的部分, 任一 Kitty 不存在时返回 None
+		/// ### Arguments
+		/// * `kitty_id_1` - 第一个 Kitty 的编号
+		/// * `kitty_id_2` - 第二个 Kitty 的编号
+		pub fn compatibility(kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> Option<u8> {
+			let kitty1 = Self::kitties(kitty_id_1)?;
+			let kitty2 = Self::kitties(kitty_id_2)?;
+
+			let (different, total) = kitty1
+				.0
+				.iter()
+				.zip(kitty2.0.iter())
+				.fold((0u32, 0u32), |(different, total), (gene_1, gene_2)| {
+					(different + (gene_1 ^ gene_2).count_ones(), total + 8)
+				});
+
+			if total == 0 {
+				return Some(0)
+			}
+
+			Some((different * 100 / total) as u8)
+		}
+
+		/// Kitty 的稀有度, 用 DNA 中为 1 的位数近似
+		/// ### Arguments
+		/// * `dna` - Kitty 的 DNA
+		pub fn rarity_score(dna: &[u8]) -> u32 {
+			dna.iter().map(|byte| byte.count_ones()).sum()
+		}
+
+		/// 解码 DNA 中的特征, DNA 太短时返回 None
+		/// ### Arguments
+		/// * `dna` - Kitty 的 DNA
+		/// * `trait_kind` - 特征
+		pub fn decode_trait(dna: &[u8], trait_kind: TraitKind) -> Option<u8> {
+			dna.get(trait_kind.gene_index()).copied()
+		}
+
+		/// 具有指定特征值的 Kitty, 按编号从小到大排列
+		/// 用于 RPC 查询, 会遍历所有 Kitty
+		/// ### Arguments
+		/// * `trait_kind` - 特征
+		/// * `value` - 特征值
+		pub fn kitties_with_trait(trait_kind: TraitKind, value: u8) -> Vec<T::KittyIndex> {
+			let mut kitties: Vec<T::KittyIndex> = Kitties::<T>::iter()
+				.filter_map(|(kitty_id, kitty)| {
+					let kitty = kitty?;
+					(Self::decode_trait(&kitty.0, trait_kind) == Some(value)).then(|| kitty_id)
+				})
+				.collect();
+
+			kitties.sort();
+			kitties
+		}
+
+		/// 稀有度最高的 Kitty, 按稀有度从高到低排列, 稀有度相同时编号小的在前
+		/// 用于 RPC 查询, 会遍历所有 Kitty
+		/// ### Arguments
+		/// * `limit` - 返回的最大数量
+		pub fn top_rare_kitties(limit: u32) -> Vec<(T::KittyIndex, u32)> {
+			let mut scores: Vec<(T::KittyIndex, u32)> = Kitties::<T>::iter()
+				.filter_map(|(kitty_id, kitty)| {
+					kitty.map(|kitty| (kitty_id, Self::rarity_score(&kitty.0)))
+				})
+				.collect();
+
+			scores.sort_by(|(id_1, score_1), (id_2, score_2)| {
+				score_2.cmp(score_1).then_with(|| id_1.cmp(id_2))
+			});
+			scores.truncate(limit as usize);
+			scores
+		}
+
+		/// Kitty 的主人
+		/// Kitty 不存在或已被销毁时返回 None
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn owner_of(kitty_id: T::KittyIndex) -> Option<T::AccountId> {
+			Owner::<T>::get(kitty_id)
+		}
+
+		/// 检查转让能否成功, 不修改存储
+		/// 与 `transfer` 执行相同的校验, 供钱包在提交交易前查询
+		/// ### Arguments
+		/// * `from` - 转让者
+		/// * `to` - 接收者
+		/// * `kitty_id` - 转让的 Kitty 编号
+		pub fn can_transfer(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			kitty_id: T::KittyIndex,
+		) -> Result<(), DispatchError> {
+			ensure!(from != to, Error::<T>::SameOwner);
+
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			ensure!(&owner == from, Error::<T>::NotOwnerOfKitty);
+			Self::ensure_not_locked(kitty_id)?;
+			Self::ensure_acquire_cooldown_passed(kitty_id)?;
+			ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			ensure!(
+				!T::RequireRecipientExists::get() ||
+					<frame_system::Pallet<T>>::account_exists(to),
+				Error::<T>::RecipientDoesNotExist
+			);
+			ensure!(Self::can_receive(to, 1), Error::<T>::RecipientAtCapacity);
+			ensure!(
+				T::Currency::can_reserve(to, Self::kitty_deposit(kitty_id)),
+				Error::<T>::NotEnoughBalance
+			);
+			Self::ensure_deposit_cap(to, Self::kitty_deposit(kitty_id))?;
+			T::TransferAuthorizer::authorize(from, to, kitty_id)?;
+
+			Ok(())
+		}
+
+		/// 账户能否再获得 `amount` 个 Kitty
+		/// ### Arguments
+		/// * `who` - 接收者
+		/// * `amount` - 获得的数量
+		pub fn can_receive(who: &T::AccountId, amount: u32) -> bool {
+			Self::owned_kitties_count(who).saturating_add(amount) <= T::MaxKittiesPerAccount::get()
+		}
+
+		/// Kitty 的年龄, 即出生以来经过的区块数
+		/// Kitty 不存在时返回 None
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn kitty_age(kitty_id: T::KittyIndex) -> Option<T::BlockNumber> {
+			let birth = Self::birth_block(kitty_id)?;
+			Some(<frame_system::Pallet<T>>::block_number().saturating_sub(birth))
+		}
+
+		/// 购买 Kitty, 返回支付的价格, 供其他 Pallet 组合使用
+		/// ### Arguments
+		/// * `buyer` - 购买者
+		/// * `kitty_id` - 购买的 Kitty 编号
+		/// * `asset_id` - 支付的资产编号, 需要与出售使用的资产相同
+		/// * `max_price` - 愿意支付的最高价格, None 表示接受任意价格
+		#[transactional]
+		pub fn do_buy(
+			buyer: T::AccountId,
+			kitty_id: T::KittyIndex,
+			asset_id: T::AssetId,
+			max_price: Option<BalanceOf<T>>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			Self::ensure_not_reentrant()?;
+
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			ensure!(owner != buyer.clone(), Error::<T>::KittyAlreadyOwned);
+
+			let (listed_asset, price) =
+				Self::kitties_price(kitty_id).ok_or(Error::<T>::NotForSale)?;
+			ensure!(listed_asset == asset_id, Error::<T>::AssetMismatch);
+			if let Some(max_price) = max_price {
+				ensure!(price <= max_price, Error::<T>::PriceExceedsMax);
+			}
+
+			Self::ensure_not_locked(kitty_id)?;
+			ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+			ensure!(Self::can_receive(&buyer, 1), Error::<T>::RecipientAtCapacity);
+
+			// 卖家账户已被删除时, 货款无处可去, 拒绝购买
+			ensure!(
+				<frame_system::Pallet<T>>::account_exists(&owner),
+				Error::<T>::SellerGone
+			);
+			T::TransferAuthorizer::authorize(&owner, &buyer, kitty_id)?;
+
+			// 质押转移到购买方
+			Self::move_deposit(&owner, &buyer, kitty_id)?;
+
+			// 转账
+			Self::pay(asset_id, &buyer, &owner, price)?;
+
+			// 出售下架
+			KittiesPrice::<T>::remove(kitty_id);
+			Self::record_sale(kitty_id, asset_id, price);
+
+			Self::transfer_kitty(owner, buyer, kitty_id, true);
+
+			Ok(price)
+		}
+
+		/// 以指定的资产转账, 资产 0 使用 `T::Currency`, 其他资产使用 `T::Assets`
+		/// 转出账户需要保持存活
+		/// ### Arguments
+		/// * `asset_id` - 资产编号
+		/// * `from` - 转出账户
+		/// * `to` - 转入账户
+		/// * `amount` - 金额
+		fn pay(
+			asset_id: T::AssetId,
+			from: &T::AccountId,
+			to: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			if asset_id.is_zero() {
+				T::Currency::transfer(from, to, amount, ExistenceRequirement::KeepAlive)
+			} else {
+				<T::Assets as fungibles::Transfer<T::AccountId>>::transfer(
+					asset_id, from, to, amount, true,
+				)
+				.map(|_| ())
+			}
+		}
+
+		/// 记录成交的区块和市场统计, 成交额只统计原生货币
+		/// ### Arguments
+		/// * `kitty_id` - 成交的 Kitty 编号
+		/// * `asset_id` - 成交使用的资产编号
+		/// * `price` - 成交价格
+		fn record_sale(kitty_id: T::KittyIndex, asset_id: T::AssetId, price: BalanceOf<T>) {
+			LastSaleBlock::<T>::insert(kitty_id, <frame_system::Pallet<T>>::block_number());
+			TotalSalesCount::<T>::mutate(|count| *count = count.saturating_add(1));
+			if asset_id.is_zero() {
+				TotalVolume::<T>::mutate(|volume| *volume = volume.saturating_add(price));
+			}
+		}
+
+		/// 下架出售中的 Kitty, 没有出售时不发出事件
+		/// 用于锁定或质押时清除不能成交的出售
+		/// ### Arguments
+		/// * `who` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		fn cancel_sale(who: &T::AccountId, kitty_id: T::KittyIndex) {
+			if KittiesPrice::<T>::take(kitty_id).is_some() {
+				Self::deposit_event(Event::KittyCancelSale(who.clone(), kitty_id));
+			}
+		}
+
+		/// Kitty 是否为传说 Kitty
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn is_legendary(kitty_id: T::KittyIndex) -> bool {
+			LegendaryKitties::<T>::contains_key(kitty_id)
+		}
+
+		/// Kitty 已经质押的区块数
+		/// 没有质押时返回 None
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn stake_duration(kitty_id: T::KittyIndex) -> Option<T::BlockNumber> {
+			let since = Self::staked_since(kitty_id)?;
+			Some(<frame_system::Pallet<T>>::block_number().saturating_sub(since))
+		}
+
+		/// 获得 Kitty 后 `T::PostAcquireTransferCooldown` 个区块内返回 `TransferCooldownActive`
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		fn ensure_acquire_cooldown_passed(kitty_id: T::KittyIndex) -> DispatchResult {
+			if let Some(acquired) = Self::acquired_at(kitty_id) {
+				ensure!(
+					<frame_system::Pallet<T>>::block_number() >=
+						acquired.saturating_add(T::PostAcquireTransferCooldown::get()),
+					Error::<T>::TransferCooldownActive
+				);
+			}
+			Ok(())
+		}
+
+		/// Kitty 处于锁定状态时返回 `KittyLocked`
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn ensure_not_locked(kitty_id: T::KittyIndex) -> DispatchResult {
+			ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+			Ok(())
+		}
+
+		/// 从到期区块的列表中移除 Kitty 的定时锁定, 不改变 `LockedKitties`
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		fn clear_lock_expiry(kitty_id: T::KittyIndex) {
+			if let Some(Some(until)) = Self::locked_kitties(kitty_id) {
+				LockExpiries::<T>::mutate(until, |expiries| expiries.retain(|id| *id != kitty_id));
+			}
+		}
+
+		/// 托管购买的审核期是否已满
+		/// ### Arguments
+		/// * `since` - 托管开始的区块
+		fn escrow_expired(since: T::BlockNumber) -> bool {
+			<frame_system::Pallet<T>>::block_number() >=
+				since.saturating_add(T::EscrowPeriod::get())
+		}
+
+		/// Kitty 是否处于锁定状态
+		/// 定时锁定在到期区块之后视为已解除, 即使 `on_initialize` 尚未清除
+		/// 托管购买中的 Kitty 也视为锁定
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		pub fn is_locked(kitty_id: T::KittyIndex) -> bool {
+			if EscrowedPurchases::<T>::contains_key(kitty_id) {
+				return true
+			}
+
+			match Self::locked_kitties(kitty_id) {
+				Some(None) => true,
+				Some(Some(until)) => <frame_system::Pallet<T>>::block_number() < until,
+				None => false,
+			}
+		}
+
+		/// 根据随机数决定是否产下双胞胎
+		/// 取随机数的前 4 个字节作为百万分比, 小于 `T::TwinRate` 时产下双胞胎
+		/// ### Arguments
+		/// * `selector` - 生产时的随机数
+		fn is_twin_birth(selector: &[u8]) -> bool {
+			let mut bytes = [0u8; 4];
+			for (byte, select) in bytes.iter_mut().zip(selector) {
+				*byte = *select;
+			}
+
+			let roll = u32::from_le_bytes(bytes);
+			Permill::from_parts(roll % 1_000_000) < T::TwinRate::get()
+		}
+
+		/// 按 `T::TraitMutationRate` 的概率突变孩子的一个特征
+		/// 由随机数的哈希决定是否突变、突变的特征和新的特征值, 与双胞胎的判断互不影响
+		/// 新的特征值在 `TraitKind::variants` 的范围内, 且与原来的值不同
+		/// DNA 中没有这个特征时不突变
+		/// ### Arguments
+		/// * `dna` - 混合后的 DNA
+		/// * `selector` - 生产时的随机数
+		fn mutate_trait(mut dna: Vec<u8>, selector: &[u8]) -> Vec<u8> {
+			let rate = T::TraitMutationRate::get();
+			if rate == Permill::zero() {
+				return dna
+			}
+
+			let roll = (selector, b"mutation").using_encoded(T::DnaHasher::hash);
+			let byte = |index: usize| roll.get(index).copied().unwrap_or_default();
+
+			let chance = u32::from_le_bytes([byte(0), byte(1), byte(2), byte(3)]);
+			if Permill::from_parts(chance % 1_000_000) >= rate {
+				return dna
+			}
+
+			let trait_kind = TraitKind::ALL[byte(4) as usize % TraitKind::ALL.len()];
+			if let Some(gene) = dna.get_mut(trait_kind.gene_index()) {
+				let variants = trait_kind.variants();
+				let value = byte(5) % variants;
+				*gene = if *gene == value { (value + 1) % variants } else { value };
+			}
+			dna
+		}
+
+		/// Kitties 总数第一次达到 `T::CountWarningThreshold` 时发出事件
+		/// ### Arguments
+		/// * `count` - 新的 Kitties 总数
+		fn check_count_warning(count: T::KittyIndex) {
+			if count < T::CountWarningThreshold::get() || Self::warning_emitted() {
+				return
+			}
+
+			WarningEmitted::<T>::put(true);
+			Self::deposit_event(Event::CountThresholdReached(count));
+		}
+
+		/// 保存新的 Kitty
+		/// ### Arguments
+		/// * `owner` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		/// * `dna` - Kitty 的 DNA
+		/// * `deposit` - 已质押的金额
+		fn mint(
+			owner: &T::AccountId,
+			kitty_id: T::KittyIndex,
+			dna: Vec<u8>,
+			deposit: BalanceOf<T>,
+		) {
+			let event_dna = if T::CompactEvents::get() { None } else { Some(dna.clone()) };
+
+			DnaIndex::<T>::insert(Self::dna_hash(&dna), kitty_id);
+			Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
+			Owner::<T>::insert(kitty_id, owner);
+			OwnedKitties::<T>::insert(owner, kitty_id, ());
+			OwnedKittiesCount::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+			Self::assign_ordinal(owner, kitty_id);
+			Self::touch(owner);
+			if !deposit.is_zero() {
+				KittyDeposits::<T>::insert(kitty_id, deposit);
+				AccountDeposits::<T>::mutate(owner, |total| *total = total.saturating_add(deposit));
+			}
+			BirthBlock::<T>::insert(kitty_id, <frame_system::Pallet<T>>::block_number());
+			GenerationCounts::<T>::mutate(0, |count| *count = count.saturating_add(1));
+			KittiesCount::<T>::put(kitty_id);
+			Self::check_count_warning(kitty_id);
+			RecentKitties::<T>::mutate(|recent| {
+				if !recent.is_empty() && recent.len() as u32 >= T::RecentWindow::get() {
+					recent.remove(0);
+				}
+				// `RecentWindow` 为 0 时不保存
+				let _ = recent.try_push(kitty_id);
+			});
+
+			Self::notify(|| T::OnKittyEvent::on_created(owner, kitty_id));
+			Self::deposit_event(Event::KittyCreated(owner.clone(), kitty_id, event_dna));
+		}
+
+		/// 随机数生成, 长度为 `T::DnaLength` 字节
+		/// 开头是随机种子的哈希, 长度不够时加上轮次后再次哈希, 哈希算法为 `T::DnaHasher`
+		/// ### Arguments
+		/// * `who` - 生成随机数的人
+		pub(crate) fn random_value(who: &T::AccountId) -> Vec<u8> {
+			let (seed, block_number) = T::Randomness::random_seed();
+			let (secondary_seed, _) = T::SecondaryRandomness::random_seed();
+			let payload = (
+				(seed ^ secondary_seed, block_number),
+				&who,
+				<frame_system::Pallet<T>>::extrinsic_index(),
+			);
+
+			Self::expand_hash(payload)
+		}
+
+		/// 由主人和编号生成确定的 DNA, 用于创世时创建的 Kitty
+		/// ### Arguments
+		/// * `owner` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		pub fn genesis_dna(owner: &T::AccountId, kitty_id: T::KittyIndex) -> Vec<u8> {
+			Self::expand_hash((b"genesis", owner, kitty_id))
+		}
+
+		/// 用 `T::DnaHasher` 把数据哈希为 `T::DnaLength` 字节
+		/// 哈希不够长时用数据和轮次继续哈希并拼接
+		/// ### Arguments
+		/// * `payload` - 哈希的数据
+		fn expand_hash(payload: impl Encode) -> Vec<u8> {
+			let length = T::DnaLength::get() as usize;
+			let mut value = payload.using_encoded(T::DnaHasher::hash);
+			let mut round: u32 = 1;
+
+			while value.len() < length {
+				value.extend_from_slice(&(&payload, round).using_encoded(T::DnaHasher::hash));
+				round += 1;
+			}
+
+			value.truncate(length);
+			value
+		}
+
+		/// 检查 Kitty 能否被销毁
+		/// ### Arguments
+		/// * `who` - 销毁者
+		/// * `kitty_id` - Kitty 编号
+		fn ensure_can_burn(who: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+			let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+			ensure!(&owner == who, Error::<T>::NotOwnerOfKitty);
+			Self::ensure_not_locked(kitty_id)?;
+			ensure!(!Staked::<T>::contains_key(kitty_id), Error::<T>::KittyStaked);
+
+			Ok(())
+		}
+
+		/// 销毁 Kitty, 清除相关存储并退还质押
+		/// 调用前需要通过 `ensure_can_burn` 校验
+		/// ### Arguments
+		/// * `owner` - Kitty 的主人
+		/// * `kitty_id` - Kitty 编号
+		fn burn_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+			let deposit = KittyDeposits::<T>::take(kitty_id);
+			if !deposit.is_zero() {
+				T::Currency::unreserve(owner, deposit);
+				AccountDeposits::<T>::mutate(owner, |total| *total = total.saturating_sub(deposit));
+			}
+
+			Owner::<T>::remove(kitty_id);
+			OwnedKitties::<T>::remove(owner, kitty_id);
+			OwnedKittiesCount::<T>::mutate(owner, |count| *count = count.saturating_sub(1));
+			OwnerOrdinal::<T>::remove(owner, kitty_id);
+			GenerationCounts::<T>::mutate(Self::generation(kitty_id), |count| {
+				*count = count.saturating_sub(1)
+			});
+			Self::clear_kitty_data(kitty_id);
+
+			Self::notify(|| T::OnKittyEvent::on_burned(owner, kitty_id));
+			Self::deposit_event(Event::KittyBurned(owner.clone(), kitty_id));
+		}
+
+		/// 设置 Kitty 的代数, 同时更新每一代的数量
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		/// * `generation` - 新的代数
+		fn set_generation(kitty_id: T::KittyIndex, generation: u32) {
+			GenerationCounts::<T>::mutate(Self::generation(kitty_id), |count| {
+				*count = count.saturating_sub(1)
+			});
+			GenerationCounts::<T>::mutate(generation, |count| *count = count.saturating_add(1));
+			Generation::<T>::insert(kitty_id, generation);
+		}
+
+		/// 删除 Kitty 自身和与主人无关的所有数据, 退还属性的质押
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		fn clear_kitty_data(kitty_id: T::KittyIndex) {
+			for (_, (depositor, deposit)) in AttributeDeposits::<T>::drain_prefix(kitty_id) {
+				T::Currency::unreserve(&depositor, deposit);
+			}
+			Attributes::<T>::drain_prefix(kitty_id).for_each(drop);
+
+			if let Some(kitty) = Kitties::<T>::take(kitty_id) {
+				let hash = Self::dna_hash(&kitty.0);
+				if Self::dna_index(hash) == Some(kitty_id) {
+					DnaIndex::<T>::remove(hash);
+				}
+			}
+			KittyDeposits::<T>::remove(kitty_id);
+			KittiesPrice::<T>::remove(kitty_id);
+			BirthBlock::<T>::remove(kitty_id);
+			LastSaleBlock::<T>::remove(kitty_id);
+			Self::clear_lock_expiry(kitty_id);
+			LockedKitties::<T>::remove(kitty_id);
+			Staked::<T>::remove(kitty_id);
+			Self::release_name(kitty_id);
+			BreedTag::<T>::remove(kitty_id);
+			Parents::<T>::remove(kitty_id);
+			Generation::<T>::remove(kitty_id);
+			BreedCooldownUntil::<T>::remove(kitty_id);
+			BreedCount::<T>::remove(kitty_id);
+			AcquiredAt::<T>::remove(kitty_id);
+			ViewCounts::<T>::remove(kitty_id);
+			LegendaryKitties::<T>::remove(kitty_id);
+			Self::leave_cattery(kitty_id);
+		}
+
+		/// 把 Kitty 移出所在的 Cattery, 不在 Cattery 中时不做任何事
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		fn leave_cattery(kitty_id: T::KittyIndex) {
+			if let Some(cattery_id) = KittyCattery::<T>::take(kitty_id) {
+				Catteries::<T>::mutate(cattery_id, |cattery| {
+					if let Some((_, _, kitties)) = cattery {
+						kitties.retain(|id| *id != kitty_id);
+					}
+				});
+				Self::deposit_event(Event::KittyRemovedFromCattery(cattery_id, kitty_id));
+			}
+		}
+
+		/// 清理没有主人的 Kitty 的遗留数据, 用于修复旧版本销毁时没有删除的数据
+		/// 从上次的位置继续检查, 权重不足时停止
+		/// ### Arguments
+		/// * `limit` - 可以使用的权重
+		fn sweep_orphans(limit: Weight) -> Weight {
+			let mut used = T::DbWeight::get().reads_writes(2, 1);
+			if used > limit {
+				return 0
+			}
+
+			let count = match Self::kitties_count() {
+				Some(count) => count,
+				None => return used,
+			};
+			let mut cursor = Self::orphan_sweep_cursor().unwrap_or_else(Zero::zero);
+			let per_kitty = T::DbWeight::get().reads_writes(6, 24);
+
+			while cursor < count && used.saturating_add(per_kitty) <= limit {
+				cursor = cursor + 1u32.into();
+				if !Owner::<T>::contains_key(cursor) {
+					Self::clear_kitty_data(cursor);
+				}
+				used = used.saturating_add(per_kitty);
+			}
+
+			OrphanSweepCursor::<T>::put(cursor);
+			used
+		}
+
+		/// 转移 Kitty 的质押金额
+		/// 接收者余额不足时返回 `NotEnoughBalance`
+		/// ### Arguments
+		/// * `from` - 原来的主人, 解除质押
+		/// * `to` - 新的主人, 质押
+		/// * `kitty_id` - Kitty 编号
+		fn move_deposit(
+			from: &T::AccountId,
+			to: &T::AccountId,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			let deposit = Self::kitty_deposit(kitty_id);
+
+			// 免费创建的 Kitty 没有质押
+			if deposit.is_zero() {
+				return Ok(())
+			}
+
+			Self::ensure_deposit_cap(to, deposit)?;
+			T::Currency::reserve(to, deposit).map_err(|_| Error::<T>::NotEnoughBalance)?;
+			T::Currency::unreserve(from, deposit);
+			AccountDeposits::<T>::mutate(from, |total| *total = total.saturating_sub(deposit));
+			AccountDeposits::<T>::mutate(to, |total| *total = total.saturating_add(deposit));
+
+			Ok(())
+		}
+
+		/// 检查账户增加质押后不超过 `T::MaxDepositPerAccount`
+		/// ### Arguments
+		/// * `who` - 质押的账户
+		/// * `amount` - 增加的质押金额
+		fn ensure_deposit_cap(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			ensure!(
+				Self::account_deposit(who).saturating_add(amount) <= T::MaxDepositPerAccount::get(),
+				Error::<T>::DepositCapExceeded
+			);
+			Ok(())
+		}
+
+		/// 创建 Kitty 时需要质押的金额
+		/// 质押金额记录在 `KittyDeposits` 中, 之后的转移和退还以记录的金额为准
+		fn creation_deposit() -> Result<BalanceOf<T>, DispatchError> {
+			if !T::RequireDeposit::get() {
+				return Ok(Zero::zero())
+			}
+
+			// 质押小于最小余额时会留下无法使用的余额
+			let deposit = T::ReserveOfNewCreate::get();
+			ensure!(deposit >= T::Currency::minimum_balance(), Error::<T>::InvalidDepositConfig);
+
+			Ok(deposit)
+		}
+
+		/// 检查质押后的可用余额不低于最小余额, 避免账户被回收
+		/// ### Arguments
+		/// * `who` - 质押的账户
+		/// * `amount` - 需要从可用余额中扣除的金额
+		fn ensure_reserve_keeps_alive(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			ensure!(
+				T::Currency::free_balance(who).saturating_sub(amount) >=
+					T::Currency::minimum_balance(),
+				Error::<T>::NotEnoughBalance
+			);
+			Ok(())
+		}
+
+		/// DNA 在 `DnaIndex` 中的键
+		/// ### Arguments
+		/// * `dna` - Kitty 的 DNA
+		pub fn dna_hash(dna: &[u8]) -> [u8; 16] {
+			blake2_128(dna)
+		}
+
+		/// 生成不重复的 DNA
+		/// DNA 已存在时重新生成随机数, 超过 `MAX_DNA_ATTEMPTS` 次返回 `DuplicateDna`
+		/// 返回最终使用的随机数和 DNA
+		/// ### Arguments
+		/// * `seed` - 第一次使用的随机数
+		/// * `make_dna` - 由随机数生成 DNA
+		fn unique_dna(
+			seed: Vec<u8>,
+			make_dna: impl Fn(&[u8]) -> Vec<u8>,
+		) -> Result<(Vec<u8>, Vec<u8>), DispatchError> {
+			let mut selector = seed;
+
+			for round in 1..=MAX_DNA_ATTEMPTS {
+				let dna = make_dna(&selector);
+				if !DnaIndex::<T>::contains_key(Self::dna_hash(&dna)) {
+					return Ok((selector, dna))
+				}
+				selector = Self::reroll(&selector, round);
+			}
+
+			Err(Error::<T>::DuplicateDna.into())
+		}
+
+		/// 由原来的随机数和轮次生成等长的新随机数
+		/// ### Arguments
+		/// * `value` - 原来的随机数
+		/// * `round` - 重新生成的轮次
+		fn reroll(value: &[u8], round: u32) -> Vec<u8> {
+			let mut rerolled = Vec::with_capacity(value.len());
+			let mut chunk: u32 = 0;
+
+			while rerolled.len() < value.len() {
+				rerolled
+					.extend_from_slice(&(value, round, chunk).using_encoded(T::DnaHasher::hash));
+				chunk += 1;
+			}
+
+			rerolled.truncate(value.len());
+			rerolled
+		}
+
+		/// 检查名字的长度
+		/// ### Arguments
+		/// * `name` - 名字
+		fn bounded_name(name: &[u8]) -> Result<BoundedVec<u8, T::MaxNameLength>, DispatchError> {
+			BoundedVec::<u8, T::MaxNameLength>::try_from(name.to_vec())
+				.map_err(|_| Error::<T>::NameTooLong.into())
+		}
+
+		/// `T::RequireUniqueNames` 为 true 时检查名字没有被其他 Kitty 使用
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		/// * `name` - 新的名字
+		fn ensure_name_available(
+			kitty_id: T::KittyIndex,
+			name: &BoundedVec<u8, T::MaxNameLength>,
+		) -> DispatchResult {
+			if T::RequireUniqueNames::get() {
+				if let Some(holder) = Self::kitty_by_name(name) {
+					ensure!(holder == kitty_id, Error::<T>::NameTaken);
+				}
+			}
+			Ok(())
+		}
+
+		/// 保存 Kitty 的名字, 并释放原来的名字
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		/// * `bounded_name` - 检查过长度的名字
+		/// * `name` - 事件中的名字
+		fn name_kitty(
+			kitty_id: T::KittyIndex,
+			bounded_name: BoundedVec<u8, T::MaxNameLength>,
+			name: Vec<u8>,
+		) {
+			Self::release_name(kitty_id);
+			NameIndex::<T>::insert(&bounded_name, kitty_id);
+			KittyNames::<T>::insert(kitty_id, bounded_name);
+			Self::deposit_event(Event::KittyNamed(kitty_id, name));
+		}
+
+		/// 删除 Kitty 的名字, 名字属于该 Kitty 时从索引中释放
+		/// ### Arguments
+		/// * `kitty_id` - Kitty 编号
+		fn release_name(kitty_id: T::KittyIndex) {
+			if let Some(old_name) = KittyNames::<T>::take(kitty_id) {
+				if Self::kitty_by_name(&old_name) == Some(kitty_id) {
+					NameIndex::<T>::remove(&old_name);
+				}
+			}
+		}
+
+		/// 收取费用的账户
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account()
+		}
+
+		/// 记录账户最后一次操作 Kitty 的区块
+		/// ### Arguments
+		/// * `who` - 账户
+		fn touch(who: &T::AccountId) {
+			LastActivity::<T>::insert(who, <frame_system::Pallet<T>>::block_number());
+		}
+
+		/// 记录 Kitty 是主人获得的第几个 Kitty
+		/// ### Arguments
+		/// * `owner` - 新的主人
+		/// * `kitty_id` - 获得的 Kitty 编号
+		fn assign_ordinal(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+			let ordinal = OwnerKittySeq::<T>::mutate(owner, |seq| {
+				*seq = seq.saturating_add(1);
+				*seq
+			});
+			OwnerOrdinal::<T>::insert(owner, kitty_id, ordinal);
+		}
+
+		/// 调用 `T::OnKittyEvent` 的回调
+		/// 回调期间修改 Kitty 归属的调用返回 `Reentrant`
+		/// ### Arguments
+		/// * `hook` - 调用回调
+		fn notify(hook: impl FnOnce()) {
+			ReentrancyGuard::<T>::put(true);
+			hook();
+			ReentrancyGuard::<T>::kill();
+		}
+
+		/// 检查是否在回调中重入
+		fn ensure_not_reentrant() -> DispatchResult {
+			ensure!(!ReentrancyGuard::<T>::get(), Error::<T>::Reentrant);
+			Ok(())
+		}
+
+		/// 转移 Kitty
+		/// ### Arguments
+		/// * `from` - 原来的主人
+		/// * `to` - 新的主人
+		/// * `kitty_id` - 转移的 Kitty 编号
+		/// * `emit_event` - 是否发出 `KittyTransfered` 事件
+		fn transfer_kitty(
+			from: T::AccountId,
+			to: T::AccountId,
+			kitty_id: T::KittyIndex,
+			emit_event: bool,
+		) {
+			Owner::<T>::insert(kitty_id, &to);
+			OwnedKitties::<T>::remove(&from, kitty_id);
+			OwnedKitties::<T>::insert(&to, kitty_id, ());
+			OwnedKittiesCount::<T>::mutate(&from, |count| *count = count.saturating_sub(1));
+			OwnedKittiesCount::<T>::mutate(&to, |count| *count = count.saturating_add(1));
+			OwnerOrdinal::<T>::remove(&from, kitty_id);
+			Self::assign_ordinal(&to, kitty_id);
+			AcquiredAt::<T>::insert(kitty_id, <frame_system::Pallet<T>>::block_number());
+			Self::leave_cattery(kitty_id);
+			Self::touch(&from);
+			Self::touch(&to);
+			Self::notify(|| T::OnKittyEvent::on_transferred(&from, &to, kitty_id));
+			if emit_event {
+				Self::deposit_event(Event::KittyTransfered(from, to, kitty_id));
+			}
 		}
 	}
 }
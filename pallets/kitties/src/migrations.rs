@@ -0,0 +1,278 @@
+//! Kitties Pallet 的存储迁移
+
+use crate::pallet::{
+	AccountDeposits, BalanceOf, Config, DnaIndex, EscrowedPurchases, Generation, GenerationCounts,
+	Kitties, KittiesPrice, KittyDeposits, Kitty, OwnedKitties, OwnedKittiesCount, Owner, Pallet,
+	Releases, StorageVersion,
+};
+use frame_support::{
+	sp_std::collections::btree_map::BTreeMap,
+	traits::{Get, ReservableCurrency},
+	weights::Weight,
+};
+use sp_runtime::traits::{Saturating, Zero};
+#[cfg(feature = "try-runtime")]
+use frame_support::{ensure, storage::unhashed};
+
+/// V2: `Owner` 的值由 `Option<AccountId>` 改为 `AccountId`
+pub mod v2 {
+	use super::*;
+
+	/// 转换 `Owner` 中的旧数据, 值为 None 的记录被删除
+	pub fn migrate<T: Config>() -> Weight {
+		let mut translated: Weight = 0;
+
+		Owner::<T>::translate::<Option<T::AccountId>, _>(|_, owner| {
+			translated += 1;
+			owner
+		});
+
+		StorageVersion::<T>::put(Releases::V2);
+
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+/// V3: `Kitty` 的 DNA 由 `[u8; 16]` 改为 `Vec<u8>`
+pub mod v3 {
+	use super::*;
+
+	/// 转换 `Kitties` 中的旧数据, 保留原有的 16 字节 DNA
+	pub fn migrate<T: Config>() -> Weight {
+		let mut translated: Weight = 0;
+
+		Kitties::<T>::translate::<Option<[u8; 16]>, _>(|_, dna| {
+			translated += 1;
+			Some(dna.map(|dna| Kitty(dna.to_vec())))
+		});
+
+		StorageVersion::<T>::put(Releases::V3);
+
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+/// V4: 增加 `DnaIndex`
+pub mod v4 {
+	use super::*;
+
+	/// 为已有的 Kitty 建立 DNA 索引
+	pub fn migrate<T: Config>() -> Weight {
+		let mut indexed: Weight = 0;
+
+		for (kitty_id, kitty) in Kitties::<T>::iter() {
+			if let Some(kitty) = kitty {
+				DnaIndex::<T>::insert(Pallet::<T>::dna_hash(&kitty.0), kitty_id);
+				indexed += 1;
+			}
+		}
+
+		StorageVersion::<T>::put(Releases::V4);
+
+		T::DbWeight::get().reads_writes(indexed + 1, indexed + 1)
+	}
+}
+
+/// V5: 增加 `OwnedKitties`
+pub mod v5 {
+	use super::*;
+
+	/// 由 `Owner` 建立每个账户拥有的 Kitty 索引
+	pub fn migrate<T: Config>() -> Weight {
+		let mut indexed: Weight = 0;
+
+		for (kitty_id, owner) in Owner::<T>::iter() {
+			OwnedKitties::<T>::insert(owner, kitty_id, ());
+			indexed += 1;
+		}
+
+		StorageVersion::<T>::put(Releases::V5);
+
+		T::DbWeight::get().reads_writes(indexed + 1, indexed + 1)
+	}
+}
+
+/// V6: 增加 `OwnedKittiesCount`
+pub mod v6 {
+	use super::*;
+
+	/// 由 `Owner` 统计每个账户拥有的 Kitty 数量
+	pub fn migrate<T: Config>() -> Weight {
+		let mut counted: Weight = 0;
+
+		for (_, owner) in Owner::<T>::iter() {
+			OwnedKittiesCount::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+			counted += 1;
+		}
+
+		StorageVersion::<T>::put(Releases::V6);
+
+		T::DbWeight::get().reads_writes(counted + 1, counted + 1)
+	}
+}
+
+/// V7: 增加 `GenerationCounts`
+pub mod v7 {
+	use super::*;
+
+	/// 由 `Owner` 和 `Generation` 统计每一代 Kitty 的数量
+	pub fn migrate<T: Config>() -> Weight {
+		let mut counted: Weight = 0;
+
+		for kitty_id in Owner::<T>::iter_keys() {
+			GenerationCounts::<T>::mutate(Generation::<T>::get(kitty_id), |count| {
+				*count = count.saturating_add(1)
+			});
+			counted += 1;
+		}
+
+		StorageVersion::<T>::put(Releases::V7);
+
+		T::DbWeight::get().reads_writes(2 * counted + 1, counted + 1)
+	}
+}
+
+/// V8: 为记录质押之前创建的 Kitty 补充 `KittyDeposits`
+pub mod v8 {
+	use super::*;
+
+	/// 没有质押记录的 Kitty 按 `T::ReserveOfNewCreate` 补充记录
+	/// 主人预留的余额不足以覆盖已记录的质押时跳过, 避免记录比实际预留更多的质押
+	pub fn migrate<T: Config>() -> Weight {
+		let deposit = T::ReserveOfNewCreate::get();
+		let mut reads: Weight = 0;
+		let mut backfilled: Weight = 0;
+
+		// 已记录的质押按主人汇总
+		let mut recorded = BTreeMap::new();
+		for (kitty_id, amount) in KittyDeposits::<T>::iter() {
+			if let Some(owner) = Owner::<T>::get(kitty_id) {
+				let total = recorded.entry(owner).or_insert_with(Default::default);
+				*total = amount.saturating_add(*total);
+			}
+			reads += 2;
+		}
+
+		for (kitty_id, owner) in Owner::<T>::iter() {
+			reads += 2;
+			if KittyDeposits::<T>::contains_key(kitty_id) {
+				continue
+			}
+
+			let total = recorded.entry(owner.clone()).or_insert_with(Default::default);
+			let required = total.saturating_add(deposit);
+			reads += 1;
+			if T::Currency::reserved_balance(&owner) >= required {
+				KittyDeposits::<T>::insert(kitty_id, deposit);
+				*total = required;
+				backfilled += 1;
+			}
+		}
+
+		StorageVersion::<T>::put(Releases::V8);
+
+		T::DbWeight::get().reads_writes(reads + 1, backfilled + 1)
+	}
+}
+
+/// V9: 增加 `AccountDeposits`
+pub mod v9 {
+	use super::*;
+
+	/// 由 `KittyDeposits` 和 `Owner` 统计每个账户的质押总额
+	pub fn migrate<T: Config>() -> Weight {
+		let mut counted: Weight = 0;
+
+		for (kitty_id, deposit) in KittyDeposits::<T>::iter() {
+			if let Some(owner) = Owner::<T>::get(kitty_id) {
+				AccountDeposits::<T>::mutate(owner, |total| *total = total.saturating_add(deposit));
+			}
+			counted += 1;
+		}
+
+		StorageVersion::<T>::put(Releases::V9);
+
+		T::DbWeight::get().reads_writes(2 * counted + 1, counted + 1)
+	}
+}
+
+/// V10: `KittiesPrice` 的价格由 `Balance` 改为 `(AssetId, Balance)`
+pub mod v10 {
+	use super::*;
+
+	/// 转换 `KittiesPrice` 中的旧数据, 已有的价格以原生货币计价, 即资产 0
+	pub fn migrate<T: Config>() -> Weight {
+		let mut translated: Weight = 0;
+
+		KittiesPrice::<T>::translate::<Option<BalanceOf<T>>, _>(|_, price| {
+			translated += 1;
+			Some(price.map(|price| (Zero::zero(), price)))
+		});
+
+		StorageVersion::<T>::put(Releases::V10);
+
+		T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+	}
+}
+
+/// V11: `EscrowedPurchases` 的值增加托管开始的区块
+pub mod v11 {
+	use super::*;
+
+	/// 转换 `EscrowedPurchases` 中的旧数据, 审核期从迁移时的区块开始计算
+	pub fn migrate<T: Config>() -> Weight {
+		let mut translated: Weight = 0;
+		let now = <frame_system::Pallet<T>>::block_number();
+
+		EscrowedPurchases::<T>::translate::<(T::AccountId, BalanceOf<T>), _>(
+			|_, (buyer, price)| {
+				translated += 1;
+				Some((buyer, price, now))
+			},
+		);
+
+		StorageVersion::<T>::put(Releases::V11);
+
+		T::DbWeight::get().reads_writes(translated + 2, translated + 1)
+	}
+}
+
+/// 迁移前有主人的 Kitty 数量, V1 中值为 None 的记录不计入
+#[cfg(feature = "try-runtime")]
+pub fn owned_kitties_before<T: Config>() -> u32 {
+	if StorageVersion::<T>::get() == Releases::V1 {
+		Owner::<T>::iter_keys()
+			.filter(|kitty_id| {
+				let key = Owner::<T>::hashed_key_for(kitty_id);
+				matches!(unhashed::get::<Option<T::AccountId>>(&key), Some(Some(_)))
+			})
+			.count() as u32
+	} else {
+		Owner::<T>::iter_keys().count() as u32
+	}
+}
+
+/// 检查迁移后 Kitty 的数量不变, 且 `OwnedKitties` 和 `OwnedKittiesCount` 与 `Owner` 一致
+/// ### Arguments
+/// * `before` - 迁移前有主人的 Kitty 数量
+#[cfg(feature = "try-runtime")]
+pub fn check_owned_kitties<T: Config>(before: u32) -> Result<(), &'static str> {
+	ensure!(StorageVersion::<T>::get() == Releases::V11, "storage version not upgraded");
+
+	let owned = Owner::<T>::iter().count() as u32;
+	ensure!(owned == before, "owned kitties count changed");
+
+	for (kitty_id, owner) in Owner::<T>::iter() {
+		ensure!(
+			OwnedKitties::<T>::contains_key(&owner, kitty_id),
+			"kitty missing from OwnedKitties"
+		);
+	}
+	ensure!(OwnedKitties::<T>::iter().count() as u32 == owned, "stale OwnedKitties entries");
+
+	let counted = OwnedKittiesCount::<T>::iter()
+		.fold(0u32, |sum, (_, count)| sum.saturating_add(count));
+	ensure!(counted == owned, "OwnedKittiesCount does not match Owner");
+
+	Ok(())
+}
@@ -1,11 +1,22 @@
 use crate as pallet_kitty;
-use frame_support::parameter_types;
+use crate::traits::{
+	AuthorizeTransfer, BitwiseBlend, Blake2Dna, BreedMixer, Consumable, DnaHasher, KittyHooks,
+	TwoxDna, ValidatePrice,
+};
+use frame_support::{
+	dispatch::DispatchResult,
+	parameter_types,
+	traits::{GenesisBuild, Get, Randomness},
+	PalletId,
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError, Permill,
 };
+use std::{cell::RefCell, collections::BTreeMap};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -18,17 +29,102 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
-		Kitties: pallet_kitty::{Pallet, Call, Storage, Event<T>},
+		Kitties: pallet_kitty::{Pallet, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
+/// 定义可以在测试中修改的配置参数
+///
+/// Used as `mock_parameter!(BreedingEnabled: bool = true)`, then `BreedingEnabled::set(false)`.
+macro_rules! mock_parameter {
+	($name:ident: $type:ty = $default:expr) => {
+		pub struct $name;
+
+		impl $name {
+			fn value() -> &'static std::thread::LocalKey<RefCell<$type>> {
+				thread_local! {
+					static VALUE: RefCell<$type> = RefCell::new($default);
+				}
+				&VALUE
+			}
+
+			/// 修改当前测试中的参数值
+			#[allow(dead_code)]
+			pub fn set(value: $type) {
+				Self::value().with(|v| *v.borrow_mut() = value);
+			}
+		}
+
+		impl Get<$type> for $name {
+			fn get() -> $type {
+				Self::value().with(|v| v.borrow().clone())
+			}
+		}
+	};
+}
+
+mock_parameter!(DnaLength: u32 = 16);
+mock_parameter!(CompactEvents: bool = false);
+mock_parameter!(ReserveOfNewCreate: u64 = 1_000_000_000);
+mock_parameter!(RequireDeposit: bool = true);
+mock_parameter!(RestrictMinting: bool = false);
+mock_parameter!(MaxKittiesPerAccount: u32 = 10);
+mock_parameter!(BreedingEnabled: bool = true);
+mock_parameter!(TwinRate: Permill = Permill::zero());
+mock_parameter!(TraitMutationRate: Permill = Permill::zero());
+mock_parameter!(InheritanceBias: u8 = 128);
+mock_parameter!(BaseBreedCooldown: u64 = 0);
+mock_parameter!(MinBreedAge: u64 = 0);
+mock_parameter!(MinValidPrice: u64 = 0);
+mock_parameter!(PickFirstParent: bool = false);
+mock_parameter!(RequireMatchingBreeds: bool = false);
+mock_parameter!(FusionEnabled: bool = true);
+mock_parameter!(ReenterOnCreate: bool = false);
+mock_parameter!(UseTwoxHasher: bool = false);
+mock_parameter!(RequireRecipientExists: bool = false);
+mock_parameter!(RequireBreedPotion: bool = false);
+mock_parameter!(RandomSeed: Option<H256> = None);
+mock_parameter!(RequireUniqueNames: bool = false);
+mock_parameter!(EmitTransferEvents: bool = true);
+mock_parameter!(MaxDepositPerAccount: u64 = u64::max_value());
+mock_parameter!(LegendaryBypassCooldown: bool = false);
+mock_parameter!(BreedReward: u64 = 0);
+mock_parameter!(MaxBreedRewards: u64 = 0);
+mock_parameter!(CountWarningThreshold: u32 = u32::max_value());
+mock_parameter!(BreedFee: u64 = 0);
+mock_parameter!(BreedCostScaling: Permill = Permill::zero());
+mock_parameter!(AllowBreedingListed: bool = false);
+mock_parameter!(PostAcquireTransferCooldown: u64 = 0);
+mock_parameter!(MaxBreedsPerAccountPerBlock: u32 = u32::max_value());
+mock_parameter!(EscrowPeriod: u64 = 10);
+mock_parameter!(BlacklistedAccount: Option<u64> = None);
+
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const SS58Prefix: u8 = 42;
 	pub const ExistentialDeposit: u64 = 1;
-	pub const ReserveOfNewCreate: u32 = 1_000_000_000;
+	pub const MaxKeyLen: u32 = 8;
+	pub const MaxValueLen: u32 = 16;
+	pub const AttributeDeposit: u64 = 10_000;
+	pub const CatteryDeposit: u64 = 500;
+	pub const SaleCooldown: u64 = 10;
+	pub const MaxBatchSize: u32 = 3;
+	pub const MaxNameLength: u32 = 8;
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+	pub const AbandonmentPeriod: u64 = 100;
+	pub const AbandonmentClaimFee: u64 = 500;
+	pub const BalanceDecimals: u8 = 12;
+	pub const MaxBreedsPerBlock: u32 = 2;
+	pub const AssetDeposit: u64 = 0;
+	pub const MetadataDeposit: u64 = 0;
+	pub const ApprovalDeposit: u64 = 0;
+	pub const StringLimit: u32 = 16;
+	pub const RecentWindow: u32 = 3;
+	pub const MaxCatterySize: u32 = 2;
+	pub const MaxLockExpiriesPerBlock: u32 = 2;
 }
 
 impl system::Config for Test {
@@ -69,18 +165,248 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDeposit;
+	type MetadataDepositPerByte = MetadataDeposit;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
 impl pallet_randomness_collective_flip::Config for Test {}
 
+/// 测试用的随机数来源, 默认使用 `RandomnessCollectiveFlip`
+/// 需要确定的 DNA 时设置种子, 例如 `RandomSeed::set(Some(H256::repeat_byte(1)))`
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		match RandomSeed::get() {
+			Some(seed) => (seed, 0),
+			None => RandomnessCollectiveFlip::random(subject),
+		}
+	}
+}
+
+/// 固定输出的第二随机数来源
+pub struct MockSecondaryRandomness;
+
+impl Randomness<H256, u64> for MockSecondaryRandomness {
+	fn random(_subject: &[u8]) -> (H256, u64) {
+		(H256::repeat_byte(0x42), 0)
+	}
+}
+
+/// 可以切换为总是继承父亲 DNA 的混合算法
+pub struct MockMixer;
+
+impl BreedMixer for MockMixer {
+	fn mix(dna_1: &[u8], dna_2: &[u8], selector: &[u8]) -> Vec<u8> {
+		if PickFirstParent::get() {
+			dna_1.to_vec()
+		} else {
+			BitwiseBlend::mix(dna_1, dna_2, selector)
+		}
+	}
+}
+
+/// 可以切换为 twox_128 的哈希算法
+pub struct MockHasher;
+
+impl DnaHasher for MockHasher {
+	fn hash(data: &[u8]) -> Vec<u8> {
+		if UseTwoxHasher::get() {
+			TwoxDna::hash(data)
+		} else {
+			Blake2Dna::hash(data)
+		}
+	}
+}
+
+/// 回调的调用记录
+#[derive(Clone, Debug, PartialEq)]
+pub enum HookCall {
+	Created(u64, u32),
+	Transferred(u64, u64, u32),
+	Burned(u64, u32),
+	/// 在回调中转让、出售和锁定刚创建的 Kitty 的结果
+	Reentered(DispatchResult),
+}
+
+thread_local! {
+	static HOOK_CALLS: RefCell<Vec<HookCall>> = RefCell::new(Vec::new());
+}
+
+/// 当前测试中回调的调用记录
+pub fn hook_calls() -> Vec<HookCall> {
+	HOOK_CALLS.with(|calls| calls.borrow().clone())
+}
+
+/// 记录所有调用的回调
+/// `ReenterOnCreate` 为 true 时, 在创建的回调中尝试把 Kitty 转让给账户 2, 出售和锁定
+pub struct MockHooks;
+
+impl KittyHooks<u64, u32> for MockHooks {
+	fn on_created(owner: &u64, kitty_id: u32) {
+		HOOK_CALLS.with(|calls| calls.borrow_mut().push(HookCall::Created(*owner, kitty_id)));
+
+		if ReenterOnCreate::get() {
+			let results = vec![
+				Kitties::transfer(Origin::signed(*owner), 2, kitty_id),
+				Kitties::sell(Origin::signed(*owner), kitty_id, Some(100)),
+				Kitties::lock(Origin::signed(*owner), kitty_id),
+			];
+			for result in results {
+				HOOK_CALLS.with(|calls| calls.borrow_mut().push(HookCall::Reentered(result)));
+			}
+		}
+	}
+
+	fn on_transferred(from: &u64, to: &u64, kitty_id: u32) {
+		HOOK_CALLS
+			.with(|calls| calls.borrow_mut().push(HookCall::Transferred(*from, *to, kitty_id)));
+	}
+
+	fn on_burned(owner: &u64, kitty_id: u32) {
+		HOOK_CALLS.with(|calls| calls.borrow_mut().push(HookCall::Burned(*owner, kitty_id)));
+	}
+}
+
+/// 拒绝低于 `MinValidPrice` 的价格
+pub struct MockPriceValidator;
+
+impl ValidatePrice<u64, u32> for MockPriceValidator {
+	fn validate(_kitty_id: u32, price: u64) -> DispatchResult {
+		if price < MinValidPrice::get() {
+			return Err(DispatchError::Other("PriceTooLow"))
+		}
+		Ok(())
+	}
+}
+
+thread_local! {
+	static BREED_POTIONS: RefCell<BTreeMap<u64, u32>> = RefCell::new(BTreeMap::new());
+}
+
+/// 给账户生产药水
+pub fn give_breed_potions(who: u64, amount: u32) {
+	BREED_POTIONS.with(|potions| *potions.borrow_mut().entry(who).or_default() += amount);
+}
+
+/// 账户剩余的生产药水
+pub fn breed_potions(who: u64) -> u32 {
+	BREED_POTIONS.with(|potions| potions.borrow().get(&who).copied().unwrap_or_default())
+}
+
+/// `RequireBreedPotion` 为 true 时, 每次生产消耗一瓶药水
+pub struct MockConsumable;
+
+impl Consumable<u64> for MockConsumable {
+	fn consume(who: &u64) -> DispatchResult {
+		if !RequireBreedPotion::get() {
+			return Ok(())
+		}
+
+		BREED_POTIONS.with(|potions| match potions.borrow_mut().get_mut(who) {
+			Some(count) if *count > 0 => {
+				*count -= 1;
+				Ok(())
+			}
+			_ => Err(DispatchError::Other("NoBreedPotion")),
+		})
+	}
+}
+
+/// 拒绝转移给 `BlacklistedAccount`
+pub struct MockAuthorizer;
+
+impl AuthorizeTransfer<u64, u32> for MockAuthorizer {
+	fn authorize(_from: &u64, to: &u64, _kitty_id: u32) -> DispatchResult {
+		if BlacklistedAccount::get() == Some(*to) {
+			return Err(DispatchError::Other("Blacklisted"))
+		}
+		Ok(())
+	}
+}
+
 impl pallet_kitty::Config for Test {
 	type Event = Event;
-	type Randomness = RandomnessCollectiveFlip;
+	type Randomness = TestRandomness;
+	type DnaLength = DnaLength;
+	type CompactEvents = CompactEvents;
+	type SecondaryRandomness = MockSecondaryRandomness;
 	type KittyIndex = u32;
 	type ReserveOfNewCreate = ReserveOfNewCreate;
+	type RequireDeposit = RequireDeposit;
+	type RestrictMinting = RestrictMinting;
 	type Currency = Balances;
+	type BreedingEnabled = BreedingEnabled;
+	type TwinRate = TwinRate;
+	type TraitMutationRate = TraitMutationRate;
+	type InheritanceBias = InheritanceBias;
+	type MaxBreedsPerBlock = MaxBreedsPerBlock;
+	type BaseBreedCooldown = BaseBreedCooldown;
+	type MinBreedAge = MinBreedAge;
+	type RequireMatchingBreeds = RequireMatchingBreeds;
+	type FusionEnabled = FusionEnabled;
+	type BreedAlgorithm = MockMixer;
+	type DnaHasher = MockHasher;
+	type OnKittyEvent = MockHooks;
+	type PriceValidator = MockPriceValidator;
+	type BreedConsumable = MockConsumable;
+	type TransferAuthorizer = MockAuthorizer;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type MaxKeyLen = MaxKeyLen;
+	type MaxValueLen = MaxValueLen;
+	type AttributeDeposit = AttributeDeposit;
+	type SaleCooldown = SaleCooldown;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxKittiesPerAccount = MaxKittiesPerAccount;
+	type PalletId = KittiesPalletId;
+	type AbandonmentPeriod = AbandonmentPeriod;
+	type AbandonmentClaimFee = AbandonmentClaimFee;
+	type BalanceDecimals = BalanceDecimals;
+	type MaxNameLength = MaxNameLength;
+	type RequireRecipientExists = RequireRecipientExists;
+	type RequireUniqueNames = RequireUniqueNames;
+	type EmitTransferEvents = EmitTransferEvents;
+	type MaxDepositPerAccount = MaxDepositPerAccount;
+	type LegendaryBypassCooldown = LegendaryBypassCooldown;
+	type AssetId = u32;
+	type Assets = Assets;
+	type BreedReward = BreedReward;
+	type MaxBreedRewards = MaxBreedRewards;
+	type CountWarningThreshold = CountWarningThreshold;
+	type BreedFee = BreedFee;
+	type BreedCostScaling = BreedCostScaling;
+	type RecentWindow = RecentWindow;
+	type AllowBreedingListed = AllowBreedingListed;
+	type PostAcquireTransferCooldown = PostAcquireTransferCooldown;
+	type ViewOracle = frame_system::EnsureRoot<u64>;
+	type MaxCatterySize = MaxCatterySize;
+	type MaxBreedsPerAccountPerBlock = MaxBreedsPerAccountPerBlock;
+	type EscrowPeriod = EscrowPeriod;
+	type MaxLockExpiriesPerBlock = MaxLockExpiriesPerBlock;
+	type CatteryDeposit = CatteryDeposit;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
+	new_test_ext_with_genesis(pallet_kitty::GenesisConfig::default())
+}
+
+/// 使用指定的 Kitties 创世配置
+pub fn new_test_ext_with_genesis(
+	kitties: pallet_kitty::GenesisConfig<Test>,
+) -> sp_io::TestExternalities {
 	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
 
 	// 初始化余额
@@ -90,6 +416,8 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	.assimilate_storage(&mut storage)
 	.unwrap();
 
+	GenesisBuild::<Test>::assimilate_storage(&kitties, &mut storage).unwrap();
+
 	let mut ext = sp_io::TestExternalities::new(storage);
 	// Events are not emitted on block 0 -> advance to block 1.
 	// Any dispatchable calls made during genesis block will have no events emitted.
@@ -1,12 +1,37 @@
 use super::*;
-use crate::mock::{new_test_ext, Event as TestEvent, Kitties, Origin, System, Test};
+use crate::mock::{
+	breed_potions, give_breed_potions, hook_calls, new_test_ext, new_test_ext_with_genesis,
+	AllowBreedingListed, Assets, AttributeDeposit, Balances, BaseBreedCooldown, BlacklistedAccount,
+	BreedCostScaling, BreedFee, BreedReward, BreedingEnabled, CompactEvents, CountWarningThreshold,
+	DnaLength, EmitTransferEvents, EscrowPeriod, Event as TestEvent, FusionEnabled, HookCall,
+	InheritanceBias, Kitties, LegendaryBypassCooldown, MaxBreedRewards, MaxBreedsPerAccountPerBlock,
+	MaxDepositPerAccount, MaxKeyLen, MaxKittiesPerAccount, MaxNameLength, MinBreedAge,
+	MinValidPrice, Origin, PickFirstParent, PostAcquireTransferCooldown, RandomSeed,
+	RandomnessCollectiveFlip, ReenterOnCreate, RequireBreedPotion, RequireDeposit,
+	RequireMatchingBreeds, RequireRecipientExists, RequireUniqueNames, ReserveOfNewCreate,
+	RestrictMinting, System, Test, TraitMutationRate, TwinRate, UseTwoxHasher,
+};
+use crate::pallet::StorageVersion;
+use crate::traits::{BitwiseBlend, BreedMixer};
 use crate::Error;
+use codec::Encode;
 use frame_support::dispatch::DispatchResult;
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::{bounded_vec::BoundedVec, unhashed},
+	traits::{
+		tokens::fungibles, IntegrityTest, OnIdle, OnInitialize, OnRuntimeUpgrade, Randomness,
+		ReservableCurrency,
+	},
+};
+use sp_core::H256;
+use sp_io::hashing::blake2_128;
+use sp_runtime::{DispatchError, Permill};
+use std::convert::TryFrom;
 
 /// Assert the given `event` exists.
 ///
-/// Used as `assert_event!(Event::KittyCreated(1, 1))`,
+/// Used as `assert_event!(Event::KittyCreated(1, 1, dna_of(1)))`,
 macro_rules! assert_event {
 	($x:expr) => {
 		System::assert_has_event(TestEvent::Kitties($x))
@@ -18,6 +43,43 @@ fn new_kitty(account_id: u64) -> DispatchResult {
 	Kitties::create(Origin::signed(account_id))
 }
 
+/// DNA of kitty, as emitted in `KittyCreated`
+fn dna_of(kitty_id: u32) -> Option<Vec<u8>> {
+	Kitties::kitties(kitty_id).map(|kitty| kitty.0)
+}
+
+/// create asset `asset_id` administered by account 1 and mint `amount` of it to `who`
+fn create_asset(asset_id: u32, who: u64, amount: u64) {
+	assert_ok!(Assets::force_create(Origin::root(), asset_id, 1, true, 1));
+	assert_ok!(Assets::mint(Origin::signed(1), asset_id, who, amount));
+}
+
+/// balance of `who` in asset `asset_id`
+fn asset_balance(asset_id: u32, who: u64) -> u64 {
+	<Assets as fungibles::Inspect<u64>>::balance(asset_id, &who)
+}
+
+/// run blocks until block `n`
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		Kitties::on_initialize(System::block_number());
+	}
+}
+
+#[test]
+fn next_kitty_id_checks_max_value() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Kitties::next_kitty_id(), Ok(1));
+
+		KittiesCount::<Test>::put(u32::max_value() - 1);
+		assert_eq!(Kitties::next_kitty_id(), Ok(u32::max_value()));
+
+		KittiesCount::<Test>::put(u32::max_value());
+		assert_noop!(Kitties::next_kitty_id(), Error::<Test>::KittiesCountOverflow);
+	});
+}
+
 #[test]
 fn create_with_max_count_overflow() {
 	new_test_ext().execute_with(|| {
@@ -31,7 +93,7 @@ fn create_test_success_with_event() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(new_kitty(1));
 		assert_eq!(KittiesCount::<Test>::get(), Some(1));
-		assert_event!(Event::KittyCreated(1, 1));
+		assert_event!(Event::KittyCreated(1, 1, dna_of(1)));
 	});
 }
 
@@ -52,146 +114,2939 @@ fn create_failed_with_not_enough_balance() {
 }
 
 #[test]
-fn transfer_success() {
+fn create_with_fixed_seed() {
+	new_test_ext().execute_with(|| {
+		let seed = H256::repeat_byte(1);
+		RandomSeed::set(Some(seed));
+
+		let expected = |who: u64| {
+			((seed ^ H256::repeat_byte(0x42), 0u64), &who, System::extrinsic_index())
+				.using_encoded(blake2_128)
+				.to_vec()
+		};
+
+		assert_ok!(new_kitty(1));
+		assert_ok!(new_kitty(2));
+		assert_eq!(dna_of(1), Some(expected(1)));
+		assert_eq!(dna_of(2), Some(expected(2)));
+	});
+}
+
+#[test]
+fn kitty_clone_equals_stored() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(new_kitty(1));
+		assert_ok!(new_kitty(1));
+
+		let kitty = Kitties::kitties(1).unwrap();
+		assert_eq!(kitty.clone(), Kitties::kitties(1).unwrap());
+		assert_ne!(kitty, Kitties::kitties(2).unwrap());
+	});
+}
+
+#[test]
+fn create_mixes_secondary_randomness() {
+	new_test_ext().execute_with(|| {
+		let single_source =
+			(RandomnessCollectiveFlip::random_seed(), &1u64, System::extrinsic_index())
+				.using_encoded(blake2_128);
+
+		assert_ok!(new_kitty(1));
+
+		let dna = Kitties::kitties(1).unwrap().0;
+		assert_eq!(dna, Kitties::random_value(&1));
+		assert_ne!(dna, single_source);
+	});
+}
+
+#[test]
+fn create_with_configured_dna_hasher() {
+	new_test_ext().execute_with(|| {
+		let blake2_dna = Kitties::random_value(&1);
+
+		UseTwoxHasher::set(true);
+		let twox_dna = Kitties::random_value(&1);
+		assert_eq!(twox_dna.len(), 16);
+		assert_ne!(twox_dna, blake2_dna);
+		assert_eq!(twox_dna, Kitties::random_value(&1));
+
+		assert_ok!(new_kitty(1));
+		assert_eq!(dna_of(1), Some(twox_dna));
+	});
+}
+
+#[test]
+fn kitty_age_since_birth() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(new_kitty(1));
+		assert_eq!(Kitties::birth_block(1), Some(1));
+		assert_eq!(Kitties::kitty_age(1), Some(0));
+
+		run_to_block(10);
+		assert_eq!(Kitties::kitty_age(1), Some(9));
+		assert_eq!(Kitties::kitty_age(2), None);
+	});
+}
+
+#[test]
+fn create_and_breed_with_longer_dna() {
 	new_test_ext().execute_with(|| {
+		DnaLength::set(32);
 		let _ = new_kitty(1);
-		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
-		assert_event!(Event::KittyTransfered(1, 2, 1));
+		let _ = new_kitty(2);
+		let _ = Kitties::transfer(Origin::signed(2), 1, 2);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		assert_eq!(Kitties::kitties(1).unwrap().0.len(), 32);
+		assert_eq!(Kitties::kitties(2).unwrap().0.len(), 32);
+		assert_eq!(Kitties::kitties(3).unwrap().0.len(), 32);
+		assert_ne!(Kitties::kitties(1).unwrap().0[..16], Kitties::kitties(1).unwrap().0[16..]);
 	});
 }
 
 #[test]
-fn transfer_fail_when_to_some_owner() {
+fn default_dna_length_is_16_bytes() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		assert_noop!(Kitties::transfer(Origin::signed(1), 1, 1), Error::<Test>::SameOwner);
+		assert_eq!(Kitties::kitties(1).unwrap().0.len(), 16);
 	});
 }
 
 #[test]
-fn transfer_fail_not_owner() {
+fn set_name_success() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		assert_noop!(Kitties::transfer(Origin::signed(2), 1, 1), Error::<Test>::NotOwnerOfKitty);
+		assert_ok!(Kitties::set_name(Origin::signed(1), 1, b"tom".to_vec()));
+
+		assert_eq!(Kitties::kitty_name(1).map(|name| name.into_inner()), Some(b"tom".to_vec()));
+		assert_event!(Event::KittyNamed(1, b"tom".to_vec()));
 	});
 }
 
 #[test]
-fn breed_success() {
+fn set_name_with_unique_names() {
 	new_test_ext().execute_with(|| {
+		RequireUniqueNames::set(true);
 		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_ok!(Kitties::set_name(Origin::signed(1), 1, b"tom".to_vec()));
+		let tom = BoundedVec::<u8, MaxNameLength>::try_from(b"tom".to_vec()).unwrap();
+		assert_eq!(Kitties::kitty_by_name(tom), Some(1));
+		// 重复设置自己的名字不受影响
+		assert_ok!(Kitties::set_name(Origin::signed(1), 1, b"tom".to_vec()));
+
+		assert_noop!(
+			Kitties::set_name(Origin::signed(2), 2, b"tom".to_vec()),
+			Error::<Test>::NameTaken
+		);
+
+		assert_ok!(Kitties::set_name(Origin::signed(1), 1, b"jerry".to_vec()));
+		assert_ok!(Kitties::set_name(Origin::signed(2), 2, b"tom".to_vec()));
+		assert_eq!(Kitties::kitty_name(2).map(|name| name.into_inner()), Some(b"tom".to_vec()));
+	});
+}
+
+#[test]
+fn transfer_with_name_success() {
+	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
+		assert_ok!(Kitties::set_name(Origin::signed(1), 1, b"tom".to_vec()));
 
-		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
-		assert_eq!(KittiesCount::<Test>::get(), Some(3));
-		assert_event!(Event::KittyCreated(1, 3));
+		assert_ok!(Kitties::transfer_with_name(Origin::signed(1), 2, 1, b"jerry".to_vec()));
+
+		assert_eq!(Kitties::owner(1), Some(2));
+		assert_eq!(Kitties::kitty_name(1).map(|name| name.into_inner()), Some(b"jerry".to_vec()));
 	});
 }
 
 #[test]
-fn breed_fail_with_same_kitty_id() {
+fn transfer_with_name_failed_with_long_name() {
 	new_test_ext().execute_with(|| {
-		assert_noop!(Kitties::breed(Origin::signed(1), 1, 1), Error::<Test>::SameParentIndex);
+		let _ = new_kitty(1);
+
+		assert_noop!(
+			Kitties::transfer_with_name(Origin::signed(1), 2, 1, b"too long name".to_vec()),
+			Error::<Test>::NameTooLong
+		);
 	});
 }
 
 #[test]
-fn breed_fail_with_invalid_index() {
+fn create_failed_when_reserve_leaves_less_than_existential_deposit() {
 	new_test_ext().execute_with(|| {
-		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::InvalidKittyIndex);
+		let deposit = ReserveOfNewCreate::get();
+		assert_ok!(Balances::transfer(Origin::signed(1), 4, deposit));
+		assert!(Balances::can_reserve(&4, deposit));
+
+		assert_noop!(new_kitty(4), Error::<Test>::NotEnoughBalance);
+
+		assert_ok!(Balances::transfer(Origin::signed(1), 4, 1));
+		assert_ok!(new_kitty(4));
+		assert_eq!(Balances::free_balance(4), 1);
 	});
 }
 
 #[test]
-fn breed_fail_with_invalid_owner() {
+fn breed_fuse_and_import_fail_when_reserve_leaves_less_than_existential_deposit() {
+	new_test_ext().execute_with(|| {
+		let deposit = ReserveOfNewCreate::get();
+		assert_ok!(Balances::transfer(Origin::signed(1), 4, deposit * 2 + 1));
+		assert_ok!(new_kitty(4));
+		assert_ok!(new_kitty(4));
+		assert_ok!(Balances::transfer(Origin::signed(1), 4, deposit - 1));
+		assert_eq!(Balances::free_balance(4), deposit);
+
+		assert_noop!(Kitties::breed(Origin::signed(4), 1, 2), Error::<Test>::NotEnoughBalance);
+		assert_noop!(Kitties::fuse(Origin::signed(4), 1, 2), Error::<Test>::NotEnoughBalance);
+		let exported = ExportedKitty { dna: vec![9u8; 16], generation: 0, parents: None };
+		assert_noop!(Kitties::import_kitty(exported, 4), Error::<Test>::NotEnoughBalance);
+
+		assert_ok!(Balances::transfer(Origin::signed(1), 4, 1));
+		assert_ok!(Kitties::breed(Origin::signed(4), 1, 2));
+		assert_eq!(Balances::free_balance(4), 1);
+	});
+}
+
+#[test]
+fn create_failed_with_reserve_below_existential_deposit() {
+	new_test_ext().execute_with(|| {
+		ReserveOfNewCreate::set(0);
+		assert_noop!(new_kitty(1), Error::<Test>::InvalidDepositConfig);
+
+		// 免费创建时不检查质押
+		RequireDeposit::set(false);
+		assert_ok!(new_kitty(1));
+	});
+}
+
+#[test]
+#[should_panic(expected = "ReserveOfNewCreate must not be less than the existential deposit")]
+fn integrity_test_rejects_reserve_below_existential_deposit() {
+	new_test_ext().execute_with(|| {
+		Kitties::integrity_test();
+		ReserveOfNewCreate::set(0);
+		Kitties::integrity_test();
+	});
+}
+
+#[test]
+fn compact_events_omit_dna() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
+		assert!(dna_of(1).is_some());
+		assert_event!(Event::KittyCreated(1, 1, dna_of(1)));
+
+		CompactEvents::set(true);
 		let _ = new_kitty(2);
+		assert_event!(Event::KittyCreated(2, 2, None));
+	});
+}
 
-		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::NotOwnerOfKitty);
+#[test]
+fn force_unreserve_releases_stuck_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::reserve(&1, 1_000));
+
+		assert_noop!(
+			Kitties::force_unreserve(Origin::signed(1), 1, 1_000),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(Kitties::force_unreserve(Origin::root(), 1, 600));
+		assert_eq!(Balances::reserved_balance(1), 400);
+		assert_event!(Event::DepositUnreserved(1, 600));
+
+		assert_ok!(Kitties::force_unreserve(Origin::root(), 1, 600));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_event!(Event::DepositUnreserved(1, 400));
 	});
 }
 
 #[test]
-fn breed_fail_with_count_overflow() {
+fn force_create_legendary_kitty() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::force_create(Origin::signed(1), 1, true), DispatchError::BadOrigin);
+
+		assert_ok!(Kitties::force_create(Origin::root(), 1, true));
+		assert_ok!(Kitties::force_create(Origin::root(), 1, false));
+		assert_eq!(Kitties::owner(1), Some(1));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert!(Kitties::is_legendary(1));
+		assert!(!Kitties::is_legendary(2));
+
+		assert_ok!(new_kitty(1));
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 3), Error::<Test>::LegendaryCannotBreed);
+		assert_ok!(Kitties::breed(Origin::signed(1), 2, 3));
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert!(!Kitties::is_legendary(1));
+	});
+}
+
+#[test]
+fn repair_owned_list_rebuilds_from_owner() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
 		let _ = new_kitty(1);
+		let _ = new_kitty(2);
 
-		KittiesCount::<Test>::put(u32::max_value());
+		OwnedKitties::<Test>::remove(1, 2);
+		OwnedKitties::<Test>::insert(1, 3, ());
+		OwnedKittiesCount::<Test>::insert(1, 7);
 
-		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::KittiesCountOverflow);
+		assert_noop!(
+			Kitties::repair_owned_list(Origin::signed(1), 1, 10),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Kitties::repair_owned_list(Origin::root(), 1, 2),
+			Error::<Test>::RepairScanExceeded
+		);
+
+		assert_ok!(Kitties::repair_owned_list(Origin::root(), 1, 10));
+		let mut owned = Kitties::kitties_of(&1);
+		owned.sort();
+		assert_eq!(owned, vec![1, 2]);
+		assert_eq!(Kitties::owned_kitties_count(1), 2);
+		assert_event!(Event::OwnedListRepaired(1, 2));
 	});
 }
 
 #[test]
-fn sell_fail_with_not_owner() {
+fn legendary_kitties_bypass_breed_cooldown() {
 	new_test_ext().execute_with(|| {
-		assert_noop!(
-			Kitties::sell(Origin::signed(1), 1, Some(100)),
-			Error::<Test>::NotOwnerOfKitty
+		BaseBreedCooldown::set(10);
+		LegendaryBypassCooldown::set(true);
+		assert_ok!(Kitties::force_create(Origin::root(), 1, true));
+		assert_ok!(Kitties::force_create(Origin::root(), 1, true));
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 3, 4));
+		assert_noop!(Kitties::breed(Origin::signed(1), 3, 4), Error::<Test>::BreedCooldownActive);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::breed_cooldown_until(1), None);
+		assert!(!Kitties::is_legendary(6));
+
+		run_to_block(2);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+	});
+}
+
+#[test]
+fn create_with_restricted_minting() {
+	new_test_ext().execute_with(|| {
+		RestrictMinting::set(true);
+		assert_noop!(new_kitty(1), Error::<Test>::NotAuthorizedToMint);
+
+		assert_ok!(Kitties::add_minter(Origin::root(), 1));
+		assert_event!(Event::MinterAdded(1));
+		assert_ok!(new_kitty(1));
+		assert_noop!(new_kitty(2), Error::<Test>::NotAuthorizedToMint);
+
+		assert_ok!(Kitties::remove_minter(Origin::root(), 1));
+		assert_noop!(new_kitty(1), Error::<Test>::NotAuthorizedToMint);
+	});
+}
+
+#[test]
+fn add_minter_requires_force_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::add_minter(Origin::signed(1), 1), DispatchError::BadOrigin);
+		assert_noop!(Kitties::remove_minter(Origin::signed(1), 1), DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn hooks_fire_on_create_transfer_and_burn() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+		assert_ok!(Kitties::burn(Origin::signed(2), 1));
+
+		assert_eq!(
+			hook_calls(),
+			vec![HookCall::Created(1, 1), HookCall::Transferred(1, 2, 1), HookCall::Burned(2, 1)]
 		);
 	});
 }
 
 #[test]
-fn sell_success() {
+fn hooks_cannot_reenter() {
+	new_test_ext().execute_with(|| {
+		ReenterOnCreate::set(true);
+		assert_ok!(new_kitty(1));
+
+		let reentered = HookCall::Reentered(Err(Error::<Test>::Reentrant.into()));
+		assert_eq!(
+			hook_calls(),
+			vec![HookCall::Created(1, 1), reentered.clone(), reentered.clone(), reentered]
+		);
+		assert_eq!(Kitties::owner(1), Some(1));
+		assert_eq!(Kitties::kitties_price(1), None);
+		assert!(!Kitties::is_locked(1));
+		assert_eq!(ReentrancyGuard::<Test>::get(), false);
+	});
+}
+
+#[test]
+fn transfer_success() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
-		assert_event!(Event::KittyForSale(1, 1, Some(100)));
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+		assert_event!(Event::KittyTransfered(1, 2, 1));
 	});
 }
 
 #[test]
-fn cancel_sell_with_none_price() {
+fn transfer_moves_deposit_to_receiver() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+		assert_eq!(Kitties::kitty_deposit(1), 1_000_000_000);
+		assert_eq!(Balances::reserved_balance(1), 1_000_000_000);
 
-		assert_ok!(Kitties::sell(Origin::signed(1), 1, None));
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
 
-		assert_eq!(None, KittiesPrice::<Test>::get(1));
-		assert_event!(Event::KittyCancelSale(1, 1));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 1_000_000_000);
 	});
 }
 
 #[test]
-fn buy_failed_when_already_owned() {
+fn transfer_fail_when_receiver_cannot_afford_deposit() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		assert_noop!(Kitties::buy(Origin::signed(1), 1), Error::<Test>::KittyAlreadyOwned);
+		assert_noop!(Kitties::transfer(Origin::signed(1), 3, 1), Error::<Test>::NotEnoughBalance);
+		assert_eq!(Kitties::owner(1), Some(1));
 	});
 }
 
 #[test]
-fn buy_fail_when_not_for_sale() {
+fn transfer_fail_when_to_some_owner() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		assert_noop!(Kitties::buy(Origin::signed(2), 1), Error::<Test>::NotForSale);
+		assert_noop!(Kitties::transfer(Origin::signed(1), 1, 1), Error::<Test>::SameOwner);
 	});
 }
 
 #[test]
-fn buy_fail_with_not_enough_balance() {
+fn transfer_fail_not_owner() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+		assert_noop!(Kitties::transfer(Origin::signed(2), 1, 1), Error::<Test>::NotOwnerOfKitty);
+	});
+}
 
-		assert_noop!(Kitties::buy(Origin::signed(3), 1), Error::<Test>::NotEnoughBalance);
+#[test]
+fn can_transfer_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::can_transfer(&1, &2, 1));
+		assert_eq!(Kitties::owner(1), Some(1));
+		assert_eq!(Balances::reserved_balance(2), 0);
 	});
 }
 
 #[test]
-fn buy_success() {
+fn can_transfer_errors() {
 	new_test_ext().execute_with(|| {
 		let _ = new_kitty(1);
-		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
 
-		assert_ok!(Kitties::buy(Origin::signed(2), 1));
+		assert_eq!(Kitties::can_transfer(&1, &1, 1), Err(Error::<Test>::SameOwner.into()));
+		assert_eq!(Kitties::can_transfer(&1, &2, 2), Err(Error::<Test>::InvalidKittyIndex.into()));
+		assert_eq!(Kitties::can_transfer(&2, &1, 1), Err(Error::<Test>::NotOwnerOfKitty.into()));
+		assert_eq!(Kitties::can_transfer(&1, &3, 1), Err(Error::<Test>::NotEnoughBalance.into()));
 
-		assert_eq!(KittiesPrice::<Test>::contains_key(1), false);
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+		assert_eq!(Kitties::can_transfer(&1, &2, 1), Err(Error::<Test>::KittyLocked.into()));
+	});
+}
 
-		assert_event!(Event::KittyTransfered(1, 2, 1));
+#[test]
+fn transfer_fail_with_invalid_index() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::InvalidKittyIndex);
+	});
+}
+
+#[test]
+fn breed_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(KittiesCount::<Test>::get(), Some(3));
+		assert_event!(Event::KittyCreated(1, 3, dna_of(3)));
+	});
+}
+
+#[test]
+fn breed_single_child_with_zero_twin_rate() {
+	new_test_ext().execute_with(|| {
+		TwinRate::set(Permill::zero());
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		assert_eq!(KittiesCount::<Test>::get(), Some(3));
+		assert_eq!(Kitties::kitty_deposit(3), 1_000_000_000);
+		assert_eq!(Balances::reserved_balance(1), 3_000_000_000);
+	});
+}
+
+#[test]
+fn breed_twins_with_full_twin_rate() {
+	new_test_ext().execute_with(|| {
+		TwinRate::set(Permill::one());
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		assert_eq!(KittiesCount::<Test>::get(), Some(4));
+		assert_eq!(Kitties::owner(4), Some(1));
+		assert_eq!(Balances::reserved_balance(1), 4_000_000_000);
+		assert_event!(Event::KittyCreated(1, 3, dna_of(3)));
+		assert_event!(Event::KittyCreated(1, 4, dna_of(4)));
+	});
+}
+
+#[test]
+fn breed_twins_skip_twin_when_index_exhausted() {
+	new_test_ext().execute_with(|| {
+		TwinRate::set(Permill::one());
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		KittiesCount::<Test>::put(u32::max_value() - 1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		assert_eq!(KittiesCount::<Test>::get(), Some(u32::max_value()));
+		assert_eq!(Kitties::owner(u32::max_value()), Some(1));
+		assert_eq!(Balances::reserved_balance(1), 3_000_000_000);
+	});
+}
+
+#[test]
+fn breed_fail_when_block_limit_reached() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::breeds_this_block(), 2);
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::BreedLimitReached);
+
+		run_to_block(2);
+		assert_eq!(Kitties::breeds_this_block(), 0);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+	});
+}
+
+#[test]
+fn breed_fail_when_breeding_disabled() {
+	new_test_ext().execute_with(|| {
+		BreedingEnabled::set(false);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::BreedingDisabled);
+	});
+}
+
+#[test]
+fn breed_success_when_breeding_enabled() {
+	new_test_ext().execute_with(|| {
+		BreedingEnabled::set(true);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::owner(3), Some(1));
+	});
+}
+
+#[test]
+fn breed_fail_with_same_kitty_id() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 1), Error::<Test>::SameParentIndex);
+	});
+}
+
+#[test]
+fn breed_fail_with_invalid_index() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::InvalidKittyIndex);
+	});
+}
+
+#[test]
+fn breed_fail_with_invalid_owner() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::NotOwnerOfKitty);
+	});
+}
+
+#[test]
+fn breed_fail_with_count_overflow() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		KittiesCount::<Test>::put(u32::max_value());
+
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::KittiesCountOverflow);
+	});
+}
+
+#[test]
+fn bitwise_blend_follows_selector() {
+	let dna_1 = [0xAAu8; 16];
+	let dna_2 = [0x55u8; 16];
+
+	assert_eq!(BitwiseBlend::mix(&dna_1, &dna_2, &[0xFF; 16]), dna_1);
+	assert_eq!(BitwiseBlend::mix(&dna_1, &dna_2, &[0x00; 16]), dna_2);
+	assert_eq!(BitwiseBlend::mix(&dna_1, &dna_2, &[0xF0; 16]), [0xA5; 16]);
+}
+
+#[test]
+fn breed_with_matching_breeds() {
+	new_test_ext().execute_with(|| {
+		RequireMatchingBreeds::set(true);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::set_breed_tag(Origin::root(), 1, 7));
+		assert_ok!(Kitties::set_breed_tag(Origin::root(), 2, 7));
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		assert_eq!(Kitties::breed_tag(3), Some(7));
+	});
+}
+
+#[test]
+fn breed_failed_with_incompatible_breeds() {
+	new_test_ext().execute_with(|| {
+		RequireMatchingBreeds::set(true);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::set_breed_tag(Origin::root(), 1, 7));
+		assert_ok!(Kitties::set_breed_tag(Origin::root(), 2, 8));
+
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::IncompatibleBreeds);
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 3), Error::<Test>::IncompatibleBreeds);
+
+		RequireMatchingBreeds::set(false);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::breed_tag(4), None);
+	});
+}
+
+#[test]
+fn set_breed_tag_requires_force_origin() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(Kitties::set_breed_tag(Origin::signed(1), 1, 7), DispatchError::BadOrigin);
+		assert_noop!(
+			Kitties::set_breed_tag(Origin::root(), 2, 7),
+			Error::<Test>::InvalidKittyIndex
+		);
+	});
+}
+
+#[test]
+fn breed_uses_configured_mixer() {
+	new_test_ext().execute_with(|| {
+		PickFirstParent::set(true);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		let _ = Kitties::transfer(Origin::signed(2), 1, 2);
+
+		let selector = Kitties::random_value(&1);
+		let dna_1 = Kitties::kitties(1).unwrap().0;
+		assert_eq!(Kitties::breed_preview(1, 2, selector), Some(dna_1));
+
+		// 孩子的 DNA 总是与父亲相同, 重新生成也无法避免重复
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::DuplicateDna);
+	});
+}
+
+#[test]
+fn breed_mutates_one_trait() {
+	new_test_ext().execute_with(|| {
+		RandomSeed::set(Some(H256::repeat_byte(7)));
+		TraitMutationRate::set(Permill::one());
+		PickFirstParent::set(true);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let dna_1 = dna_of(1).unwrap();
+
+		// 孩子的 DNA 与父亲相同, 只有突变的特征不同
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		let child = dna_of(3).unwrap();
+		let changed: Vec<usize> =
+			(0..child.len()).filter(|index| child[*index] != dna_1[*index]).collect();
+		assert_eq!(changed.len(), 1);
+		let kind = TraitKind::ALL.iter().find(|kind| kind.gene_index() == changed[0]).unwrap();
+		assert!(child[changed[0]] < kind.variants());
+		assert_eq!(child.len(), dna_1.len());
+	});
+}
+
+#[test]
+fn breed_reward_stops_at_cap() {
+	new_test_ext().execute_with(|| {
+		BreedReward::set(30);
+		MaxBreedRewards::set(50);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let total_of = |who: u64| Balances::free_balance(who) + Balances::reserved_balance(who);
+		let balance = total_of(1);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_event!(Event::BreedRewardPaid(1, 30));
+		assert_eq!(total_of(1), balance + 30);
+
+		// 只奖励剩余的 20
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_event!(Event::BreedRewardPaid(1, 20));
+		assert_eq!(Kitties::total_breed_rewards(), 50);
+		assert_eq!(total_of(1), balance + 50);
+
+		// 奖励用尽后照常生产, 但不再铸造奖励
+		run_to_block(2);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::owner(5), Some(1));
+		assert!(Kitties::kitties(5).is_some());
+		assert_eq!(total_of(1), balance + 50);
+		assert_eq!(Kitties::total_breed_rewards(), 50);
+	});
+}
+
+#[test]
+fn breed_fee_rises_with_parent_breed_count() {
+	new_test_ext().execute_with(|| {
+		BreedFee::set(100);
+		BreedCostScaling::set(Permill::from_percent(50));
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_eq!(Kitties::breed_fee(1, 2), 100);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::breed_count(1), 1);
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 100);
+
+		// 按生产次数较多的父母计算
+		assert_eq!(Kitties::breed_fee(1, 3), 150);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 3));
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 250);
+		assert_eq!(Kitties::breed_fee(1, 2), 200);
+		assert_eq!(Kitties::breed_fee(2, 3), 150);
+	});
+}
+
+#[test]
+fn breed_fail_when_account_block_limit_reached() {
+	new_test_ext().execute_with(|| {
+		MaxBreedsPerAccountPerBlock::set(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		let _ = new_kitty(2);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::BreedLimitReached);
+
+		// 其它账户不受影响
+		assert_ok!(Kitties::breed(Origin::signed(2), 3, 4));
+
+		run_to_block(2);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::account_breeds(1), (2, 1));
+	});
+}
+
+#[test]
+fn breed_fail_when_parent_listed() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::ParentListedForSale);
+
+		AllowBreedingListed::set(true);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::kitties_price(2), Some((0, 100)));
+	});
+}
+
+#[test]
+fn breed_preview_matches_breed() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		let selector = Kitties::random_value(&1);
+		let preview = Kitties::breed_preview(1, 2, selector);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(preview, Kitties::kitties(3).map(|kitty| kitty.0));
+	});
+}
+
+#[test]
+fn inheritance_bias_tilts_toward_first_parent() {
+	new_test_ext().execute_with(|| {
+		crate::Kitties::<Test>::insert(1, Some(Kitty(vec![0xAA; 16])));
+		crate::Kitties::<Test>::insert(2, Some(Kitty(vec![0x55; 16])));
+		let selector: Vec<u8> = (0..16).map(|i| i * 16).collect();
+		let from_first_parent = |bias: u8| {
+			InheritanceBias::set(bias);
+			let dna = Kitties::breed_preview(1, 2, selector.clone()).unwrap();
+			dna.iter().filter(|byte| **byte == 0xAA).count()
+		};
+
+		assert_eq!(from_first_parent(0), 0);
+		assert_eq!(from_first_parent(128), 8);
+		assert_eq!(from_first_parent(255), 16);
+	});
+}
+
+#[test]
+fn breed_preview_with_missing_parent() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_eq!(Kitties::breed_preview(1, 2, vec![0u8; 16]), None);
+		assert_eq!(KittiesCount::<Test>::get(), Some(1));
+	});
+}
+
+#[test]
+fn sell_fail_with_not_owner() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Kitties::sell(Origin::signed(1), 1, Some(100)),
+			Error::<Test>::NotOwnerOfKitty
+		);
+	});
+}
+
+#[test]
+fn breed_registered_pair() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::register_pair(Origin::signed(1), 1, 2));
+		assert_eq!(Kitties::breeding_pairs(1), Some((1, 2)));
+
+		assert_ok!(Kitties::breed_registered(Origin::signed(1)));
+		assert_eq!(Kitties::owner(3), Some(1));
+		assert_event!(Event::KittyCreated(1, 3, dna_of(3)));
+	});
+}
+
+#[test]
+fn register_pair_failed() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_noop!(
+			Kitties::register_pair(Origin::signed(1), 1, 1),
+			Error::<Test>::SameParentIndex
+		);
+		assert_noop!(
+			Kitties::register_pair(Origin::signed(1), 1, 2),
+			Error::<Test>::NotOwnerOfKitty
+		);
+	});
+}
+
+#[test]
+fn breed_registered_failed_without_pair() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::breed_registered(Origin::signed(1)), Error::<Test>::NoBreedingPair);
+	});
+}
+
+#[test]
+fn sell_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_event!(Event::KittyForSale(1, 1, Some((0, 100))));
+	});
+}
+
+#[test]
+fn sell_first_listing_emits_for_sale() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_event!(Event::KittyForSale(1, 1, Some((0, 100))));
+		assert!(!System::events()
+			.iter()
+			.any(|r| matches!(r.event, TestEvent::Kitties(Event::KittyPriceChanged(..)))));
+	});
+}
+
+#[test]
+fn sell_again_emits_price_changed() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(200)));
+
+		assert_eq!(Some((0, 200)), KittiesPrice::<Test>::get(1));
+		assert_event!(Event::KittyPriceChanged(1, 1, (0, 100), (0, 200)));
+	});
+}
+
+#[test]
+fn cancel_sell_with_none_price() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, None));
+
+		assert_eq!(None, KittiesPrice::<Test>::get(1));
+		assert_event!(Event::KittyCancelSale(1, 1));
+	});
+}
+
+#[test]
+fn floor_price_returns_cheapest_listing() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Kitties::floor_price(), None);
+
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(300));
+		let _ = Kitties::sell(Origin::signed(1), 2, Some(100));
+		let _ = Kitties::sell(Origin::signed(1), 3, Some(200));
+
+		assert_eq!(Kitties::floor_price(), Some((2, 100)));
+
+		let _ = Kitties::sell(Origin::signed(1), 2, None);
+		assert_eq!(Kitties::floor_price(), Some((3, 200)));
+	});
+}
+
+#[test]
+fn sell_honors_price_validator() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		MinValidPrice::set(100);
+
+		assert_noop!(
+			Kitties::sell(Origin::signed(1), 1, Some(99)),
+			DispatchError::Other("PriceTooLow")
+		);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+
+		// 取消出售不检查价格
+		MinValidPrice::set(1_000);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, None));
+	});
+}
+
+#[test]
+fn buy_failed_when_already_owned() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(Kitties::buy(Origin::signed(1), 1, None), Error::<Test>::KittyAlreadyOwned);
+	});
+}
+
+#[test]
+fn buy_fail_when_not_for_sale() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(Kitties::buy(Origin::signed(2), 1, None), Error::<Test>::NotForSale);
+	});
+}
+
+#[test]
+fn buy_fail_with_not_enough_balance() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+
+		assert_noop!(Kitties::buy(Origin::signed(3), 1, None), Error::<Test>::NotEnoughBalance);
+	});
+}
+
+#[test]
+fn buy_fail_when_price_unaffordable_after_deposit() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(1_000));
+		assert_ok!(Balances::transfer(Origin::signed(1), 4, ReserveOfNewCreate::get() + 101));
+
+		// 质押已转移到购买者, 支付货款失败后必须一并回滚
+		assert_noop!(
+			Kitties::buy(Origin::signed(4), 1, None),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_eq!(Kitties::kitty_deposit(1), ReserveOfNewCreate::get());
+		assert_eq!(Balances::reserved_balance(4), 0);
+	});
+}
+
+#[test]
+fn buy_fail_when_seller_reaped() {
+	new_test_ext().execute_with(|| {
+		RequireDeposit::set(false);
+		let _ = new_kitty(3);
+		let _ = Kitties::sell(Origin::signed(3), 1, Some(100));
+
+		assert_ok!(Balances::transfer(Origin::signed(3), 1, Balances::free_balance(3)));
+		assert!(!System::account_exists(&3));
+
+		assert_noop!(Kitties::buy(Origin::signed(2), 1, None), Error::<Test>::SellerGone);
+	});
+}
+
+#[test]
+fn sell_fail_within_sale_cooldown() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, None));
+		assert_eq!(Kitties::last_sale_block(1), Some(1));
+
+		run_to_block(10);
+		assert_noop!(
+			Kitties::sell(Origin::signed(2), 1, Some(200)),
+			Error::<Test>::SaleCooldownActive
+		);
+
+		run_to_block(11);
+		assert_ok!(Kitties::sell(Origin::signed(2), 1, Some(200)));
+	});
+}
+
+#[test]
+fn buy_fail_with_invalid_index() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::buy(Origin::signed(2), 1, None), Error::<Test>::InvalidKittyIndex);
+	});
+}
+
+#[test]
+fn buy_with_max_price() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+
+		assert_noop!(
+			Kitties::buy(Origin::signed(2), 1, Some(99)),
+			Error::<Test>::PriceExceedsMax
+		);
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, Some(100)));
+		assert_eq!(Kitties::owner(1), Some(2));
+	});
+}
+
+#[test]
+fn buy_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 1, Some(100));
+
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, None));
+
+		assert_eq!(KittiesPrice::<Test>::contains_key(1), false);
+
+		assert_event!(Event::KittyTransfered(1, 2, 1));
+	});
+}
+
+#[test]
+fn do_buy_returns_paid_price() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		let seller_balance = Balances::free_balance(1);
+
+		assert_noop!(Kitties::do_buy(2, 1, 0, Some(99)), Error::<Test>::PriceExceedsMax);
+		assert_eq!(Kitties::do_buy(2, 1, 0, None), Ok(100));
+
+		assert_eq!(Kitties::owner(1), Some(2));
+		assert_eq!(Kitties::kitties_price(1), None);
+		assert_eq!(Balances::free_balance(1), seller_balance + 100 + ReserveOfNewCreate::get());
+	});
+}
+
+#[test]
+fn sell_and_buy_in_different_assets() {
+	new_test_ext().execute_with(|| {
+		create_asset(1, 2, 1_000);
+		create_asset(2, 2, 1_000);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::sell_in_asset(Origin::signed(1), 1, 1, Some(100)));
+		assert_event!(Event::KittyForSale(1, 1, Some((1, 100))));
+		assert_ok!(Kitties::sell_in_asset(Origin::signed(1), 2, 2, Some(300)));
+		assert_eq!(Kitties::kitties_price(2), Some((2, 300)));
+
+		// 支付的资产需要与出售使用的资产相同
+		assert_noop!(Kitties::buy(Origin::signed(2), 1, None), Error::<Test>::AssetMismatch);
+		assert_noop!(
+			Kitties::buy_in_asset(Origin::signed(2), 1, 2, None),
+			Error::<Test>::AssetMismatch
+		);
+		assert_noop!(
+			Kitties::buy_in_asset(Origin::signed(2), 1, 1, Some(99)),
+			Error::<Test>::PriceExceedsMax
+		);
+
+		let native_balance = Balances::free_balance(2);
+		assert_ok!(Kitties::buy_in_asset(Origin::signed(2), 1, 1, Some(100)));
+		assert_ok!(Kitties::buy_in_asset(Origin::signed(2), 2, 2, None));
+
+		assert_eq!(Kitties::owner(1), Some(2));
+		assert_eq!(Kitties::owner(2), Some(2));
+		assert_eq!(Kitties::kitties_price(1), None);
+		assert_eq!((asset_balance(1, 1), asset_balance(1, 2)), (100, 900));
+		assert_eq!((asset_balance(2, 1), asset_balance(2, 2)), (300, 700));
+		// 原生货币只用于转移质押, 成交额只统计原生货币
+		assert_eq!(Balances::free_balance(2), native_balance - 2 * ReserveOfNewCreate::get());
+		assert_eq!(Kitties::market_stats(), (2, 0));
+	});
+}
+
+#[test]
+fn asset_listings_excluded_from_native_only_paths() {
+	new_test_ext().execute_with(|| {
+		create_asset(1, 2, 1_000);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell_in_asset(Origin::signed(1), 1, 1, Some(50)));
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+
+		assert_eq!(Kitties::floor_price(), Some((2, 100)));
+		assert_eq!(Kitties::portfolio_listed_value(&1), 100);
+		// 托管购买只支持原生货币
+		assert_noop!(Kitties::initiate_buy(Origin::signed(2), 1), Error::<Test>::AssetNotSupported);
+
+		// 以资产 0 出售与 `sell` 相同
+		assert_ok!(Kitties::sell_in_asset(Origin::signed(1), 1, 0, Some(80)));
+		assert_event!(Event::KittyPriceChanged(1, 1, (1, 50), (0, 80)));
+		assert_eq!(Kitties::floor_price(), Some((1, 80)));
+		assert_ok!(Kitties::initiate_buy(Origin::signed(2), 1));
+	});
+}
+
+#[test]
+fn escrowed_buy_confirm_works() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		let seller_balance = Balances::free_balance(1);
+		let buyer_balance = Balances::free_balance(2);
+
+		assert_ok!(Kitties::initiate_buy(Origin::signed(2), 1));
+		assert_event!(Event::PurchaseEscrowed(2, 1, 100));
+		assert_eq!(Kitties::escrowed_purchase(1), Some((2, 100, 1)));
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 100);
+		assert_eq!(KittiesPrice::<Test>::contains_key(1), false);
+		assert!(Kitties::is_locked(1));
+
+		assert_noop!(Kitties::transfer(Origin::signed(1), 3, 1), Error::<Test>::KittyLocked);
+		assert_noop!(Kitties::initiate_buy(Origin::signed(3), 1), Error::<Test>::EscrowPending);
+		assert_noop!(Kitties::confirm_buy(Origin::signed(3), 1), Error::<Test>::NotEscrowBuyer);
+
+		assert_ok!(Kitties::confirm_buy(Origin::signed(2), 1));
+		assert_event!(Event::PurchaseConfirmed(2, 1));
+		assert_eq!(Kitties::owner(1), Some(2));
+		assert_eq!(Kitties::escrowed_purchase(1), None);
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 0);
+		assert_eq!(Balances::free_balance(1), seller_balance + 100);
+		assert_eq!(Balances::free_balance(2), buyer_balance - 100);
+		assert_eq!(Kitties::market_stats(), (1, 100));
+		assert!(!Kitties::is_locked(1));
+	});
+}
+
+#[test]
+fn stake_fail_during_escrow() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::initiate_buy(Origin::signed(2), 1));
+
+		assert_noop!(Kitties::stake(Origin::signed(1), 1), Error::<Test>::KittyLocked);
+
+		assert_ok!(Kitties::confirm_buy(Origin::signed(2), 1));
+		assert_eq!(Kitties::staked_since(1), None);
+	});
+}
+
+#[test]
+fn escrowed_buy_cancel_refunds() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		let buyer_balance = Balances::free_balance(2);
+
+		assert_ok!(Kitties::initiate_buy(Origin::signed(2), 1));
+		assert_noop!(Kitties::cancel_buy(Origin::signed(3), 1), Error::<Test>::EscrowNotExpired);
+
+		assert_ok!(Kitties::cancel_buy(Origin::signed(2), 1));
+		assert_event!(Event::PurchaseCancelled(2, 1));
+		assert_eq!(Kitties::owner(1), Some(1));
+		assert_eq!(Kitties::escrowed_purchase(1), None);
+		assert_eq!(Balances::free_balance(2), buyer_balance);
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 0);
+		assert!(!Kitties::is_locked(1));
+
+		assert_noop!(Kitties::confirm_buy(Origin::signed(2), 1), Error::<Test>::NoEscrow);
+
+		// 卖家也可以取消, 货款退还给购买者
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::initiate_buy(Origin::signed(2), 1));
+		assert_ok!(Kitties::cancel_buy(Origin::signed(1), 1));
+		assert_event!(Event::PurchaseCancelled(2, 1));
+		assert_eq!(Balances::free_balance(2), buyer_balance);
+	});
+}
+
+#[test]
+fn escrowed_buy_expires_after_period() {
+	new_test_ext().execute_with(|| {
+		EscrowPeriod::set(5);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		let buyer_balance = Balances::free_balance(2);
+		assert_ok!(Kitties::initiate_buy(Origin::signed(2), 1));
+
+		// 审核期内其他人不能取消
+		run_to_block(5);
+		assert_noop!(Kitties::cancel_buy(Origin::signed(3), 1), Error::<Test>::EscrowNotExpired);
+
+		// 审核期满后购买者不能再确认, 任何人都可以取消并退款给购买者
+		run_to_block(6);
+		assert_noop!(Kitties::confirm_buy(Origin::signed(2), 1), Error::<Test>::EscrowExpired);
+		assert_ok!(Kitties::cancel_buy(Origin::signed(3), 1));
+		assert_event!(Event::PurchaseCancelled(2, 1));
+		assert_eq!(Kitties::owner(1), Some(1));
+		assert_eq!(Balances::free_balance(2), buyer_balance);
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 0);
+	});
+}
+
+#[test]
+fn transfer_authorizer_blocks_blacklisted_recipient() {
+	new_test_ext().execute_with(|| {
+		BlacklistedAccount::set(Some(2));
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		let blocked = DispatchError::Other("Blacklisted");
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), blocked);
+
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_noop!(Kitties::buy(Origin::signed(2), 1, None), blocked);
+		assert_noop!(Kitties::initiate_buy(Origin::signed(2), 1), blocked);
+
+		assert_ok!(Kitties::transfer(Origin::signed(1), 3, 2));
+		assert_ok!(Kitties::buy(Origin::signed(3), 1, None));
+		assert_eq!(Kitties::owner(1), Some(3));
+	});
+}
+
+#[test]
+fn recent_kitties_keeps_latest_window() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_eq!(Kitties::recent_kitties().into_inner(), vec![1, 2]);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		let _ = new_kitty(2);
+		let _ = new_kitty(2);
+		assert_eq!(Kitties::recent_kitties().into_inner(), vec![3, 4, 5]);
+	});
+}
+
+#[test]
+fn next_index_matches_assigned_index() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Kitties::next_index(), 1);
+		assert_ok!(Kitties::create(Origin::signed(1)));
+		assert_eq!(Kitties::owner(1), Some(1));
+
+		let next = Kitties::next_index();
+		assert_eq!(next, 2);
+		assert_ok!(Kitties::create(Origin::signed(2)));
+		assert_eq!(Kitties::owner(next), Some(2));
+		assert_eq!(Kitties::next_index(), 3);
+	});
+}
+
+#[test]
+fn transfer_all_moves_holdings_in_batches() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..6 {
+			let _ = new_kitty(1);
+		}
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::lock(Origin::signed(1), 6));
+		let last_batch = || {
+			System::events()
+				.iter()
+				.rev()
+				.find_map(|record| match record.event {
+					TestEvent::Kitties(Event::KittiesTransferredAll(.., moved, skipped, next)) =>
+						Some((moved, skipped, next)),
+					_ => None,
+				})
+				.unwrap()
+		};
+
+		// 每次最多读取 MaxBatchSize 个, 锁定的 Kitty 被跳过并单独计数
+		assert_ok!(Kitties::transfer_all(Origin::signed(1), 2, None));
+		let (moved_1, skipped_1, next) = last_batch();
+		assert_eq!(moved_1 + skipped_1, 3);
+		assert!(next.is_some());
+
+		assert_ok!(Kitties::transfer_all(Origin::signed(1), 2, next));
+		let (moved_2, skipped_2, next) = last_batch();
+		assert_eq!((moved_1 + moved_2, skipped_1 + skipped_2), (5, 1));
+		assert_event!(Event::KittyTransfered(1, 2, 1));
+		assert_eq!(Kitties::kitties_price(1), None);
+		assert_eq!(Kitties::owner(6), Some(1));
+
+		assert_ok!(Kitties::transfer_all(Origin::signed(1), 2, next));
+		assert_eq!(last_batch(), (0, 0, None));
+
+		assert_ok!(Kitties::unlock(Origin::signed(1), 6));
+		assert_ok!(Kitties::transfer_all(Origin::signed(1), 2, None));
+		assert_eq!(last_batch(), (1, 0, None));
+		assert_eq!(Kitties::owned_kitties_count(1), 0);
+		assert!(Kitties::kitties_of(&1).is_empty());
+		assert_eq!(Kitties::owned_kitties_count(2), 6);
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn transfer_and_sell_wait_for_acquire_cooldown() {
+	new_test_ext().execute_with(|| {
+		PostAcquireTransferCooldown::set(5);
+		let _ = new_kitty(1);
+
+		// 创建的 Kitty 没有冷却期
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, None));
+		assert_eq!(Kitties::acquired_at(1), Some(1));
+
+		run_to_block(5);
+		assert_noop!(
+			Kitties::transfer(Origin::signed(2), 3, 1),
+			Error::<Test>::TransferCooldownActive
+		);
+		assert_noop!(
+			Kitties::sell(Origin::signed(2), 1, Some(100)),
+			Error::<Test>::TransferCooldownActive
+		);
+
+		run_to_block(6);
+		assert_ok!(Kitties::transfer(Origin::signed(2), 1, 1));
+
+		// 转让后重新计时, 出售还需要等待 `SaleCooldown`
+		run_to_block(11);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, None));
+
+		// 购买后重新计时
+		assert_noop!(
+			Kitties::transfer(Origin::signed(2), 3, 1),
+			Error::<Test>::TransferCooldownActive
+		);
+		run_to_block(16);
+		assert_ok!(Kitties::transfer(Origin::signed(2), 3, 1));
+	});
+}
+
+#[test]
+fn transfer_keep_listing_retains_price() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::transfer(Origin::signed(1), 3, 3_000_000_000));
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+
+		// 普通转让会下架
+		assert_ok!(Kitties::transfer(Origin::signed(1), 3, 2));
+		assert_eq!(Kitties::kitties_price(2), None);
+
+		assert_ok!(Kitties::transfer_keep_listing(Origin::signed(1), 3, 1));
+		assert_eq!(Kitties::owner(1), Some(3));
+		assert_eq!(Kitties::kitties_price(1), Some((0, 100)));
+
+		let seller_balance = Balances::free_balance(1);
+		let consignee_balance = Balances::free_balance(3);
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, None));
+		assert_event!(Event::KittyTransfered(3, 2, 1));
+		assert_eq!(Balances::free_balance(1), seller_balance);
+		assert_eq!(
+			Balances::free_balance(3),
+			consignee_balance + 100 + ReserveOfNewCreate::get()
+		);
+	});
+}
+
+#[test]
+fn lock_prevents_transfer_and_sell() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyLocked);
+		assert_noop!(Kitties::sell(Origin::signed(1), 1, Some(100)), Error::<Test>::KittyLocked);
+
+		run_to_block(100);
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyLocked);
+
+		assert_ok!(Kitties::unlock(Origin::signed(1), 1));
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+	});
+}
+
+#[test]
+fn ensure_not_locked_works() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::ensure_not_locked(1));
+
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 5));
+		assert_noop!(Kitties::ensure_not_locked(1), Error::<Test>::KittyLocked);
+
+		run_to_block(5);
+		assert_ok!(Kitties::ensure_not_locked(1));
+	});
+}
+
+#[test]
+fn lock_blocks_every_mutating_path() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyLocked);
+		assert_noop!(Kitties::sell(Origin::signed(1), 1, Some(100)), Error::<Test>::KittyLocked);
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::KittyLocked);
+		assert_noop!(Kitties::breed(Origin::signed(1), 2, 1), Error::<Test>::KittyLocked);
+		assert_noop!(Kitties::burn(Origin::signed(1), 1), Error::<Test>::KittyLocked);
+
+		// 锁定会下架 Kitty, 直接写入价格检查购买
+		KittiesPrice::<Test>::insert(1, Some((0, 100)));
+		assert_noop!(Kitties::buy(Origin::signed(2), 1, None), Error::<Test>::KittyLocked);
+	});
+}
+
+#[test]
+fn lock_fail_with_not_owner() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(Kitties::lock(Origin::signed(2), 1), Error::<Test>::NotOwnerOfKitty);
+		assert_noop!(Kitties::lock_until(Origin::signed(2), 1, 5), Error::<Test>::NotOwnerOfKitty);
+	});
+}
+
+#[test]
+fn lock_until_fail_with_past_block() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(
+			Kitties::lock_until(Origin::signed(1), 1, 1),
+			Error::<Test>::InvalidLockExpiry
+		);
+	});
+}
+
+#[test]
+fn lock_until_expires() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 5));
+
+		run_to_block(4);
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyLocked);
+
+		run_to_block(5);
+		assert_eq!(LockedKitties::<Test>::get(1), None);
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+	});
+}
+
+#[test]
+fn lock_changes_emit_events() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+		assert_event!(Event::KittyLockChanged(1, true));
+
+		assert_ok!(Kitties::unlock(Origin::signed(1), 1));
+		assert_event!(Event::KittyLockChanged(1, false));
+
+		System::reset_events();
+		assert_ok!(Kitties::unlock(Origin::signed(1), 1));
+		assert!(System::events().is_empty());
+
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 5));
+		assert_event!(Event::KittyLockChanged(1, true));
+
+		System::reset_events();
+		run_to_block(5);
+		assert_event!(Event::KittyLockChanged(1, false));
+	});
+}
+
+#[test]
+fn stake_prevents_transfer() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(Kitties::stake(Origin::signed(2), 1), Error::<Test>::NotOwnerOfKitty);
+		assert_noop!(Kitties::unstake(Origin::signed(1), 1), Error::<Test>::NotStaked);
+
+		assert_ok!(Kitties::stake(Origin::signed(1), 1));
+		assert_event!(Event::KittyStaked(1, 1));
+		assert_noop!(Kitties::stake(Origin::signed(1), 1), Error::<Test>::KittyStaked);
+
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyStaked);
+		assert_noop!(Kitties::sell(Origin::signed(1), 1, Some(100)), Error::<Test>::KittyStaked);
+		assert_noop!(Kitties::burn(Origin::signed(1), 1), Error::<Test>::KittyStaked);
+
+		assert_ok!(Kitties::unstake(Origin::signed(1), 1));
+		assert_event!(Event::KittyUnstaked(1, 1));
+		assert_eq!(Kitties::staked_since(1), None);
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+	});
+}
+
+#[test]
+fn deposit_cap_limits_mints_and_purchases() {
+	new_test_ext().execute_with(|| {
+		MaxDepositPerAccount::set(2 * ReserveOfNewCreate::get());
+		assert_ok!(new_kitty(1));
+		assert_ok!(new_kitty(1));
+		assert_eq!(Kitties::account_deposit(1), 2 * ReserveOfNewCreate::get());
+		assert_noop!(new_kitty(1), Error::<Test>::DepositCapExceeded);
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::DepositCapExceeded);
+
+		assert_ok!(new_kitty(2));
+		assert_ok!(Kitties::sell(Origin::signed(2), 3, Some(100)));
+		assert_noop!(Kitties::buy(Origin::signed(1), 3, None), Error::<Test>::DepositCapExceeded);
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert_eq!(Kitties::account_deposit(1), ReserveOfNewCreate::get());
+		assert_ok!(Kitties::buy(Origin::signed(1), 3, None));
+		assert_eq!(Kitties::account_deposit(1), 2 * ReserveOfNewCreate::get());
+		assert_eq!(Kitties::account_deposit(2), 0);
+	});
+}
+
+#[test]
+fn transfer_without_events() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+		assert_event!(Event::KittyTransfered(1, 2, 1));
+
+		EmitTransferEvents::set(false);
+		assert_ok!(Kitties::transfer(Origin::signed(2), 1, 1));
+		assert!(!System::events()
+			.iter()
+			.any(|record| record.event == TestEvent::Kitties(Event::KittyTransfered(2, 1, 1))));
+
+		// 购买仍然发出事件
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+		assert_ok!(Kitties::buy(Origin::signed(2), 2, None));
+		assert_event!(Event::KittyTransfered(1, 2, 2));
+	});
+}
+
+#[test]
+fn transfer_to_missing_account() {
+	new_test_ext().execute_with(|| {
+		RequireDeposit::set(false);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::transfer(Origin::signed(1), 99, 1));
+		assert_eq!(Kitties::owner(1), Some(99));
+
+		RequireRecipientExists::set(true);
+		assert_noop!(
+			Kitties::transfer(Origin::signed(1), 98, 2),
+			Error::<Test>::RecipientDoesNotExist
+		);
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 2));
+	});
+}
+
+#[test]
+fn lock_and_stake_cancel_listing() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+		assert_eq!(Kitties::kitties_price(1), None);
+		assert_event!(Event::KittyCancelSale(1, 1));
+
+		assert_ok!(Kitties::stake(Origin::signed(1), 2));
+		assert_eq!(Kitties::kitties_price(2), None);
+		assert_event!(Event::KittyCancelSale(1, 2));
+		assert_noop!(Kitties::buy(Origin::signed(2), 2, None), Error::<Test>::NotForSale);
+	});
+}
+
+#[test]
+fn stake_tracks_duration() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_eq!(Kitties::stake_duration(1), None);
+
+		run_to_block(3);
+		assert_ok!(Kitties::stake(Origin::signed(1), 1));
+		assert_eq!(Kitties::staked_since(1), Some(3));
+
+		run_to_block(10);
+		assert_eq!(Kitties::stake_duration(1), Some(7));
+	});
+}
+
+#[test]
+fn lock_until_expired_before_cleanup() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 5));
+
+		System::set_block_number(5);
+		assert_eq!(LockedKitties::<Test>::get(1), Some(Some(5)));
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+	});
+}
+
+#[test]
+fn relock_is_not_cleared_by_stale_expiry() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 5));
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+		assert!(LockExpiries::<Test>::get(5).is_empty());
+
+		run_to_block(5);
+		assert_noop!(Kitties::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyLocked);
+	});
+}
+
+#[test]
+fn lock_expiries_are_bounded_per_block() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 5));
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 2, 5));
+		assert_noop!(
+			Kitties::lock_until(Origin::signed(1), 3, 5),
+			Error::<Test>::TooManyLockExpiries
+		);
+
+		// 重新锁定和解除锁定都会移出原来的到期区块
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 1, 6));
+		assert_ok!(Kitties::unlock(Origin::signed(1), 2));
+		assert!(LockExpiries::<Test>::get(5).is_empty());
+		assert_eq!(LockExpiries::<Test>::get(6).into_inner(), vec![1]);
+		assert_ok!(Kitties::lock_until(Origin::signed(1), 3, 5));
+	});
+}
+
+#[test]
+fn set_kitties_count_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::set_kitties_count(Origin::root(), 10));
+
+		assert_eq!(KittiesCount::<Test>::get(), Some(10));
+		assert_event!(Event::KittiesCountReset(10));
+
+		assert_ok!(new_kitty(1));
+		assert_eq!(Kitties::owner(11), Some(1));
+	});
+}
+
+#[test]
+fn set_kitties_count_fail_when_lowering() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_noop!(
+			Kitties::set_kitties_count(Origin::root(), 1),
+			Error::<Test>::KittiesCountTooLow
+		);
+	});
+}
+
+#[test]
+fn set_kitties_count_fail_with_bad_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::set_kitties_count(Origin::signed(1), 10), DispatchError::BadOrigin);
+	});
+}
+
+/// get attribute value of a kitty
+fn attribute(kitty_id: u32, key: &[u8]) -> Option<Vec<u8>> {
+	let key = BoundedVec::<u8, MaxKeyLen>::try_from(key.to_vec()).unwrap();
+	Kitties::attributes(kitty_id, key).map(|value| value.into_inner())
+}
+
+/// 检查 Kitty 的数据已全部删除
+fn assert_kitty_data_cleared(kitty_id: u32) {
+	assert_eq!(Kitties::kitties(kitty_id), None);
+	assert_eq!(Kitties::kitty_deposit(kitty_id), 0);
+	assert_eq!(Kitties::kitties_price(kitty_id), None);
+	assert_eq!(Kitties::birth_block(kitty_id), None);
+	assert_eq!(Kitties::last_sale_block(kitty_id), None);
+	assert_eq!(Kitties::kitty_name(kitty_id), None);
+	assert_eq!(Kitties::breed_tag(kitty_id), None);
+	assert_eq!(Kitties::parents(kitty_id), None);
+	assert_eq!(Kitties::generation(kitty_id), 0);
+	assert_eq!(Kitties::breed_cooldown_until(kitty_id), None);
+	assert_eq!(Kitties::breed_count(kitty_id), 0);
+	assert_eq!(Kitties::acquired_at(kitty_id), None);
+	assert_eq!(Kitties::view_count(kitty_id), 0);
+	assert_eq!(attribute(kitty_id, b"color"), None);
+	assert_eq!(AttributeDeposits::<Test>::iter_prefix(kitty_id).count(), 0);
+}
+
+#[test]
+fn burn_clears_all_kitty_data() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::set_breed_tag(Origin::root(), 1, 7));
+		assert_ok!(Kitties::set_breed_tag(Origin::root(), 2, 7));
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		assert_ok!(Kitties::set_name(Origin::signed(1), 3, b"tom".to_vec()));
+		assert_ok!(Kitties::set_attribute(
+			Origin::signed(1),
+			3,
+			b"color".to_vec(),
+			b"red".to_vec()
+		));
+		assert_ok!(Kitties::sell(Origin::signed(1), 3, Some(100)));
+		assert_ok!(Kitties::buy(Origin::signed(2), 3, None));
+
+		let reserved = Balances::reserved_balance(1);
+		assert_ok!(Kitties::burn(Origin::signed(2), 3));
+
+		assert_kitty_data_cleared(3);
+		assert_eq!(Balances::reserved_balance(1), reserved - AttributeDeposit::get());
+	});
+}
+
+#[test]
+fn on_idle_sweeps_orphaned_data() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::set_name(Origin::signed(1), 1, b"tom".to_vec()));
+		assert_ok!(Kitties::set_attribute(
+			Origin::signed(1),
+			1,
+			b"color".to_vec(),
+			b"red".to_vec()
+		));
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+
+		// 旧版本销毁时只删除了主人
+		Owner::<Test>::remove(1);
+		OwnedKitties::<Test>::remove(1, 1);
+
+		Kitties::on_idle(1, u64::max_value());
+		assert_kitty_data_cleared(1);
+		assert!(Kitties::kitties(2).is_some());
+		assert_eq!(Kitties::orphan_sweep_cursor(), Some(2));
+	});
+}
+
+#[test]
+fn set_attribute_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::set_attribute(Origin::signed(1), 1, b"level".to_vec(), b"1".to_vec()));
+
+		assert_eq!(attribute(1, b"level"), Some(b"1".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 1_000_010_000);
+		assert_event!(Event::AttributeSet(1, b"level".to_vec(), b"1".to_vec()));
+	});
+}
+
+#[test]
+fn set_attribute_overwrite_without_extra_deposit() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::set_attribute(Origin::signed(1), 1, b"level".to_vec(), b"1".to_vec());
+
+		assert_ok!(Kitties::set_attribute(Origin::signed(1), 1, b"level".to_vec(), b"2".to_vec()));
+
+		assert_eq!(attribute(1, b"level"), Some(b"2".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 1_000_010_000);
+	});
+}
+
+#[test]
+fn clear_attribute_refunds_deposit() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::set_attribute(Origin::signed(1), 1, b"level".to_vec(), b"1".to_vec());
+		let _ = Kitties::set_attribute(Origin::signed(1), 1, b"xp".to_vec(), b"100".to_vec());
+		assert_eq!(Balances::reserved_balance(1), 1_000_020_000);
+
+		assert_ok!(Kitties::clear_attribute(Origin::signed(1), 1, b"level".to_vec()));
+
+		assert_eq!(attribute(1, b"level"), None);
+		assert_eq!(attribute(1, b"xp"), Some(b"100".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), 1_000_010_000);
+		assert_event!(Event::AttributeCleared(1, b"level".to_vec()));
+
+		assert_noop!(
+			Kitties::clear_attribute(Origin::signed(1), 1, b"level".to_vec()),
+			Error::<Test>::AttributeNotFound
+		);
+	});
+}
+
+#[test]
+fn set_attribute_fail_with_not_owner() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(
+			Kitties::set_attribute(Origin::signed(2), 1, b"level".to_vec(), b"1".to_vec()),
+			Error::<Test>::NotOwnerOfKitty
+		);
+	});
+}
+
+#[test]
+fn set_attribute_fail_with_too_long_key_or_value() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(
+			Kitties::set_attribute(Origin::signed(1), 1, b"too_long_key".to_vec(), b"1".to_vec()),
+			Error::<Test>::AttributeKeyTooLong
+		);
+		assert_noop!(
+			Kitties::set_attribute(Origin::signed(1), 1, b"level".to_vec(), [0u8; 17].to_vec()),
+			Error::<Test>::AttributeValueTooLong
+		);
+	});
+}
+
+#[test]
+fn burn_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = Kitties::set_attribute(Origin::signed(1), 1, b"level".to_vec(), b"1".to_vec());
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+
+		assert_eq!(Kitties::owner(1), None);
+		assert!(Kitties::kitties(1).is_none());
+		assert_eq!(attribute(1, b"level"), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_event!(Event::KittyBurned(1, 1));
+	});
+}
+
+#[test]
+fn burn_fail_with_not_owner_or_locked() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(Kitties::burn(Origin::signed(2), 1), Error::<Test>::NotOwnerOfKitty);
+		assert_noop!(Kitties::burn(Origin::signed(1), 2), Error::<Test>::InvalidKittyIndex);
+
+		let _ = Kitties::lock(Origin::signed(1), 1);
+		assert_noop!(Kitties::burn(Origin::signed(1), 1), Error::<Test>::KittyLocked);
+	});
+}
+
+#[test]
+fn burn_many_success() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::burn_many(Origin::signed(1), vec![1, 3]));
+
+		assert_eq!(Kitties::owner(1), None);
+		assert_eq!(Kitties::owner(2), Some(1));
+		assert_eq!(Kitties::owner(3), None);
+		assert_eq!(Balances::reserved_balance(1), 1_000_000_000);
+		assert_event!(Event::KittyBurned(1, 1));
+		assert_event!(Event::KittyBurned(1, 3));
+	});
+}
+
+#[test]
+fn burn_many_clears_listed_kitty() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = Kitties::sell(Origin::signed(1), 2, Some(100));
+
+		assert_ok!(Kitties::burn_many(Origin::signed(1), vec![1, 2]));
+
+		assert_eq!(KittiesPrice::<Test>::contains_key(2), false);
+	});
+}
+
+#[test]
+fn burn_many_is_all_or_nothing() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_noop!(
+			Kitties::burn_many(Origin::signed(1), vec![1, 2]),
+			Error::<Test>::NotOwnerOfKitty
+		);
+		assert_eq!(Kitties::owner(1), Some(1));
+	});
+}
+
+#[test]
+fn burn_many_fail_with_invalid_batch() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_noop!(
+			Kitties::burn_many(Origin::signed(1), vec![1, 2, 3, 4]),
+			Error::<Test>::TooManyKitties
+		);
+		assert_noop!(
+			Kitties::burn_many(Origin::signed(1), vec![1, 1]),
+			Error::<Test>::DuplicateKittyIndex
+		);
+	});
+}
+
+#[test]
+fn emit_snapshot_marker_success() {
+	new_test_ext().execute_with(|| {
+		run_to_block(5);
+		assert_ok!(Kitties::emit_snapshot_marker(Origin::root()));
+		assert_event!(Event::SnapshotMarker(5));
+	});
+}
+
+#[test]
+fn emit_snapshot_marker_fail_with_bad_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Kitties::emit_snapshot_marker(Origin::signed(1)), DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn kitties_with_trait_filters_by_gene() {
+	new_test_ext().execute_with(|| {
+		crate::Kitties::<Test>::insert(1, Some(Kitty(vec![3, 7, 0, 0])));
+		crate::Kitties::<Test>::insert(2, Some(Kitty(vec![5, 7, 0, 0])));
+		crate::Kitties::<Test>::insert(3, Some(Kitty(vec![3, 1])));
+		crate::Kitties::<Test>::insert(4, None);
+
+		assert_eq!(Kitties::kitties_with_trait(TraitKind::Color, 3), vec![1, 3]);
+		assert_eq!(Kitties::kitties_with_trait(TraitKind::Eyes, 7), vec![1, 2]);
+		assert_eq!(Kitties::kitties_with_trait(TraitKind::Size, 0), vec![1, 2]);
+		assert!(Kitties::kitties_with_trait(TraitKind::Pattern, 9).is_empty());
+	});
+}
+
+#[test]
+fn owner_of_existing_missing_and_burned() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_eq!(Kitties::owner_of(1), Some(1));
+		assert_eq!(Kitties::owner_of(2), Some(2));
+		assert_eq!(Kitties::owner_of(3), None);
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert_eq!(Kitties::owner_of(1), None);
+	});
+}
+
+#[test]
+fn genesis_sets_latest_storage_version() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V11);
+	});
+}
+
+#[test]
+fn genesis_mints_derived_dna() {
+	let genesis = || GenesisConfig::<Test> {
+		kitties: vec![(1, vec![]), (1, vec![]), (2, vec![7; 16])],
+		derive_dna: true,
+	};
+
+	let mut first = None;
+	new_test_ext_with_genesis(genesis()).execute_with(|| {
+		assert_eq!(Kitties::kitties_count(), Some(3));
+		assert_eq!(Kitties::owner(2), Some(1));
+		assert_eq!(Kitties::owner(3), Some(2));
+		assert_eq!(dna_of(1), Some(Kitties::genesis_dna(&1, 1)));
+		assert_eq!(dna_of(2), Some(Kitties::genesis_dna(&1, 2)));
+		assert_ne!(dna_of(1), dna_of(2));
+		assert_eq!(dna_of(3), Some(vec![7; 16]));
+		assert_eq!(Kitties::kitty_deposit(1), 0);
+		first = dna_of(1);
+	});
+
+	// 相同的配置生成相同的 DNA
+	new_test_ext_with_genesis(genesis()).execute_with(|| {
+		assert_eq!(dna_of(1), first);
+	});
+}
+
+#[test]
+fn migrate_owner_to_option_query() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V1);
+		unhashed::put(&Owner::<Test>::hashed_key_for(1), &Some(1u64));
+		unhashed::put(&Owner::<Test>::hashed_key_for(2), &None::<u64>);
+		unhashed::put(&Owner::<Test>::hashed_key_for(3), &Some(3u64));
+
+		crate::migrations::v2::migrate::<Test>();
+
+		assert_eq!(Kitties::owner_of(1), Some(1));
+		assert_eq!(Owner::<Test>::contains_key(2), false);
+		assert_eq!(Kitties::owner_of(3), Some(3));
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+	});
+}
+
+#[test]
+fn create_and_breed_without_deposit() {
+	new_test_ext().execute_with(|| {
+		RequireDeposit::set(false);
+
+		assert_ok!(new_kitty(3));
+		assert_ok!(new_kitty(3));
+		assert_ok!(Kitties::breed(Origin::signed(3), 1, 2));
+
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(Kitties::kitty_deposit(3), 0);
+		assert_eq!(KittyDeposits::<Test>::contains_key(1), false);
+	});
+}
+
+#[test]
+fn burn_without_deposit_refunds_nothing() {
+	new_test_ext().execute_with(|| {
+		RequireDeposit::set(false);
+		let _ = new_kitty(3);
+
+		assert_ok!(Kitties::burn(Origin::signed(3), 1));
+
+		assert_eq!(Balances::reserved_balance(3), 0);
+		assert_eq!(Balances::free_balance(3), 900_000_000);
+	});
+}
+
+#[test]
+fn kitty_with_deposit_keeps_it_after_toggle() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		RequireDeposit::set(false);
+		let _ = new_kitty(1);
+		assert_eq!(Balances::reserved_balance(1), 1_000_000_000);
+
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 2));
+		assert_eq!(Balances::reserved_balance(2), 0);
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn migrate_kitty_dna_to_vec() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V2);
+		unhashed::put(&crate::Kitties::<Test>::hashed_key_for(1), &Some([7u8; 16]));
+		unhashed::put(&crate::Kitties::<Test>::hashed_key_for(2), &None::<[u8; 16]>);
+
+		crate::migrations::v3::migrate::<Test>();
+
+		assert_eq!(Kitties::kitties(1).unwrap().0, vec![7u8; 16]);
+		assert!(Kitties::kitties(2).is_none());
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+	});
+}
+
+#[test]
+fn create_rerolls_duplicate_dna() {
+	new_test_ext().execute_with(|| {
+		// 同一个区块中同一个账户的随机数相同
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		let dna_1 = Kitties::kitties(1).unwrap().0;
+		let dna_2 = Kitties::kitties(2).unwrap().0;
+		assert_eq!(dna_1, Kitties::random_value(&1));
+		assert_ne!(dna_1, dna_2);
+
+		assert_eq!(Kitties::dna_index(Kitties::dna_hash(&dna_1)), Some(1));
+		assert_eq!(Kitties::dna_index(Kitties::dna_hash(&dna_2)), Some(2));
+	});
+}
+
+#[test]
+fn burn_removes_dna_index() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let dna = Kitties::kitties(1).unwrap().0;
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert_eq!(Kitties::dna_index(Kitties::dna_hash(&dna)), None);
+
+		// 销毁后同样的 DNA 可以再次使用
+		assert_ok!(new_kitty(1));
+		assert_eq!(Kitties::kitties(2).unwrap().0, dna);
+		assert_eq!(Kitties::dna_index(Kitties::dna_hash(&dna)), Some(2));
+	});
+}
+
+#[test]
+fn migrate_builds_dna_index() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V3);
+		crate::Kitties::<Test>::insert(1, Some(Kitty(vec![7u8; 16])));
+
+		crate::migrations::v4::migrate::<Test>();
+
+		assert_eq!(Kitties::dna_index(Kitties::dna_hash(&[7u8; 16])), Some(1));
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V4);
+	});
+}
+
+#[test]
+fn owned_kitties_follow_owner() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+
+		assert_eq!(Kitties::kitties_of(&1), vec![2]);
+		assert_eq!(Kitties::kitties_of(&2), vec![1]);
+
+		assert_ok!(Kitties::burn(Origin::signed(2), 1));
+		assert_eq!(Kitties::kitties_of(&2), Vec::<u32>::new());
+	});
+}
+
+#[test]
+fn portfolio_listed_value_sums_listed_kitties() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::sell(Origin::signed(1), 3, Some(250)));
+		assert_ok!(Kitties::sell(Origin::signed(2), 4, Some(1000)));
+
+		assert_eq!(Kitties::portfolio_listed_value(&1), 350);
+		assert_eq!(Kitties::portfolio_listed_value(&2), 1000);
+		assert_eq!(Kitties::portfolio_listed_value(&3), 0);
+	});
+}
+
+#[test]
+fn migrate_builds_owned_kitties() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V4);
+		Owner::<Test>::insert(1, 3);
+
+		crate::migrations::v5::migrate::<Test>();
+
+		assert_eq!(Kitties::kitties_of(&3), vec![1]);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V5);
+	});
+}
+
+#[test]
+fn top_rare_kitties_ordered_by_score() {
+	new_test_ext().execute_with(|| {
+		crate::Kitties::<Test>::insert(1, Some(Kitty(vec![0x01; 16])));
+		crate::Kitties::<Test>::insert(2, Some(Kitty(vec![0xFF; 16])));
+		crate::Kitties::<Test>::insert(3, Some(Kitty(vec![0x0F; 16])));
+		crate::Kitties::<Test>::insert(4, Some(Kitty(vec![0xF0; 16])));
+
+		assert_eq!(Kitties::top_rare_kitties(3), vec![(2, 128), (3, 64), (4, 64)]);
+		assert_eq!(Kitties::top_rare_kitties(10).len(), 4);
+		assert_eq!(Kitties::top_rare_kitties(0), vec![]);
+	});
+}
+
+#[test]
+fn kitties_of_owner_with_several_and_none() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		let mut kitties = Kitties::kitties_of(&1);
+		kitties.sort();
+		assert_eq!(kitties, vec![1, 3, 4]);
+		assert_eq!(Kitties::kitties_of(&3), Vec::<u32>::new());
+	});
+}
+
+#[test]
+fn breed_records_lineage() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_ok!(Kitties::breed(Origin::signed(1), 3, 1));
+
+		assert_eq!(Kitties::generation(1), 0);
+		assert_eq!(Kitties::parents(1), None);
+		assert_eq!(Kitties::generation(3), 1);
+		assert_eq!(Kitties::parents(3), Some((1, 2)));
+		assert_eq!(Kitties::generation(4), 2);
+		assert_eq!(Kitties::parents(4), Some((3, 1)));
+	});
+}
+
+#[test]
+fn export_then_import_preserves_dna_and_lineage() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		let dna = Kitties::kitties(3).unwrap().0;
+
+		let exported = Kitties::export_kitty(3).unwrap();
+		assert_eq!(
+			exported,
+			ExportedKitty { dna: dna.clone(), generation: 1, parents: Some((1, 2)) }
+		);
+		assert_eq!(Kitties::owner(3), None);
+		assert_eq!(Kitties::generation(3), 0);
+
+		let kitty_id = Kitties::import_kitty(exported, 2).unwrap();
+		assert_eq!(kitty_id, 4);
+		assert_eq!(Kitties::owner(4), Some(2));
+		assert_eq!(Kitties::kitties(4).unwrap().0, dna);
+		assert_eq!(Kitties::generation(4), 1);
+		assert_eq!(Kitties::parents(4), Some((1, 2)));
+		assert_eq!(Balances::reserved_balance(2), 1_000_000_000);
+	});
+}
+
+#[test]
+fn import_kitty_failed_with_duplicate_dna() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let exported =
+			ExportedKitty { dna: Kitties::kitties(1).unwrap().0, generation: 0, parents: None };
+
+		assert_noop!(Kitties::import_kitty(exported, 2), Error::<Test>::DuplicateDna);
+	});
+}
+
+#[test]
+fn import_kitty_failed_with_invalid_dna_length() {
+	new_test_ext().execute_with(|| {
+		let short = ExportedKitty { dna: vec![1u8; 8], generation: 0, parents: None };
+		assert_noop!(Kitties::import_kitty(short, 2), Error::<Test>::InvalidDnaLength);
+
+		let long = ExportedKitty { dna: vec![1u8; 17], generation: 0, parents: None };
+		assert_noop!(Kitties::import_kitty(long, 2), Error::<Test>::InvalidDnaLength);
+	});
+}
+
+#[test]
+fn fuse_burns_parents_and_creates_child() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_eq!(Balances::reserved_balance(1), 2_000_000_000);
+
+		assert_ok!(Kitties::fuse(Origin::signed(1), 1, 2));
+
+		assert_eq!(Kitties::owner(1), None);
+		assert_eq!(Kitties::owner(2), None);
+		assert_eq!(Kitties::owner(3), Some(1));
+		assert_eq!(Kitties::parents(3), Some((1, 2)));
+		assert_eq!(Kitties::generation(3), 1);
+		assert_eq!(Kitties::kitty_deposit(3), 1_000_000_000);
+		assert_eq!(Balances::reserved_balance(1), 1_000_000_000);
+		assert_eq!(Balances::free_balance(1), 9_000_000_000);
+	});
+}
+
+#[test]
+fn fuse_failed() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_noop!(Kitties::fuse(Origin::signed(1), 1, 1), Error::<Test>::SameParentIndex);
+		assert_noop!(Kitties::fuse(Origin::signed(1), 1, 3), Error::<Test>::NotOwnerOfKitty);
+
+		assert_ok!(Kitties::lock(Origin::signed(1), 2));
+		assert_noop!(Kitties::fuse(Origin::signed(1), 1, 2), Error::<Test>::KittyLocked);
+
+		FusionEnabled::set(false);
+		assert_noop!(Kitties::fuse(Origin::signed(1), 1, 2), Error::<Test>::FusionDisabled);
+	});
+}
+
+#[test]
+fn fuse_fail_with_breed_restrictions() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Kitties::force_create(Origin::root(), 1, true));
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_noop!(Kitties::fuse(Origin::signed(1), 1, 2), Error::<Test>::LegendaryCannotBreed);
+
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+		assert_noop!(Kitties::fuse(Origin::signed(1), 2, 3), Error::<Test>::ParentListedForSale);
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, None));
+
+		assert_ok!(Kitties::stake(Origin::signed(1), 3));
+		assert_noop!(Kitties::fuse(Origin::signed(1), 2, 3), Error::<Test>::KittyStaked);
+		assert_ok!(Kitties::unstake(Origin::signed(1), 3));
+
+		MinBreedAge::set(5);
+		assert_noop!(Kitties::fuse(Origin::signed(1), 2, 3), Error::<Test>::KittyTooYoung);
+	});
+}
+
+#[test]
+fn owner_ordinals_increment_per_owner() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+
+		assert_eq!(Kitties::owner_ordinal(1, 1), Some(1));
+		assert_eq!(Kitties::owner_ordinal(1, 2), Some(2));
+		assert_eq!(Kitties::owner_ordinal(2, 3), Some(1));
+
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+		assert_eq!(Kitties::owner_ordinal(1, 1), None);
+		assert_eq!(Kitties::owner_ordinal(2, 1), Some(2));
+
+		// 重新获得的 Kitty 也使用新的序号
+		assert_ok!(Kitties::transfer(Origin::signed(2), 1, 1));
+		assert_eq!(Kitties::owner_ordinal(1, 1), Some(3));
+		assert_eq!(Kitties::owner_kitty_seq(1), 3);
+		assert_eq!(Kitties::owner_kitty_seq(2), 2);
+	});
+}
+
+#[test]
+fn recipient_at_capacity_rejects_inbound_kitties() {
+	new_test_ext().execute_with(|| {
+		MaxKittiesPerAccount::set(2);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		let _ = new_kitty(2);
+		assert_eq!(Kitties::owned_kitties_count(1), 2);
+
+		assert_noop!(new_kitty(1), Error::<Test>::RecipientAtCapacity);
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::RecipientAtCapacity);
+		assert_noop!(
+			Kitties::transfer(Origin::signed(2), 1, 3),
+			Error::<Test>::RecipientAtCapacity
+		);
+
+		assert_ok!(Kitties::sell(Origin::signed(2), 4, Some(100)));
+		assert_noop!(
+			Kitties::buy(Origin::signed(1), 4, None),
+			Error::<Test>::RecipientAtCapacity
+		);
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert_eq!(Kitties::owned_kitties_count(1), 1);
+		assert_ok!(Kitties::transfer(Origin::signed(2), 1, 3));
+		assert_eq!(Kitties::owned_kitties_count(1), 2);
+	});
+}
+
+#[test]
+fn migrate_counts_owned_kitties() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V5);
+		Owner::<Test>::insert(1, 3);
+		Owner::<Test>::insert(2, 3);
+
+		crate::migrations::v6::migrate::<Test>();
+
+		assert_eq!(Kitties::owned_kitties_count(3), 2);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V6);
+	});
+}
+
+#[test]
+fn migrate_counts_generations() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V6);
+		Owner::<Test>::insert(1, 3);
+		Owner::<Test>::insert(2, 3);
+		Owner::<Test>::insert(3, 3);
+		Generation::<Test>::insert(3, 2);
+
+		crate::migrations::v7::migrate::<Test>();
+
+		assert_eq!(Kitties::generation_count(0), 2);
+		assert_eq!(Kitties::generation_count(2), 1);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V7);
+	});
+}
+
+#[test]
+fn migrate_sums_account_deposits() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V8);
+		Owner::<Test>::insert(1, 3);
+		Owner::<Test>::insert(2, 3);
+		KittyDeposits::<Test>::insert(1, 100);
+		KittyDeposits::<Test>::insert(2, 50);
+
+		crate::migrations::v9::migrate::<Test>();
+
+		assert_eq!(Kitties::account_deposit(3), 150);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V9);
+	});
+}
+
+#[test]
+fn migrate_backfills_legacy_kitty_deposits() {
+	new_test_ext().execute_with(|| {
+		// 记录质押之前创建的 Kitty 只有预留的余额, 没有质押记录
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		for kitty_id in 1..=3 {
+			KittyDeposits::<Test>::remove(kitty_id);
+		}
+		AccountDeposits::<Test>::remove(1);
+		AccountDeposits::<Test>::remove(2);
+		// 预留的余额不足时不补充记录
+		Balances::unreserve(&2, 1);
+		StorageVersion::<Test>::put(Releases::V7);
+
+		Kitties::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V11);
+		assert_eq!(Kitties::kitty_deposit(1), 1_000_000_000);
+		assert_eq!(Kitties::kitty_deposit(2), 1_000_000_000);
+		assert_eq!(Kitties::kitty_deposit(3), 0);
+		assert_eq!(Kitties::account_deposit(1), 2_000_000_000);
+		assert_eq!(Kitties::account_deposit(2), 0);
+
+		// 补充记录后, 转让时质押随 Kitty 转移
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+		assert_eq!(Balances::reserved_balance(1), 1_000_000_000);
+		assert_eq!(Balances::reserved_balance(2), 1_999_999_999);
+		assert_eq!(Kitties::account_deposit(2), 1_000_000_000);
+	});
+}
+
+#[test]
+fn migrate_prices_to_native_asset() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V9);
+		unhashed::put(&KittiesPrice::<Test>::hashed_key_for(1), &Some(100u64));
+		unhashed::put(&KittiesPrice::<Test>::hashed_key_for(2), &None::<u64>);
+
+		Kitties::on_runtime_upgrade();
+
+		assert_eq!(Kitties::kitties_price(1), Some((0, 100)));
+		assert_eq!(Kitties::kitties_price(2), None);
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V11);
+	});
+}
+
+#[test]
+fn migrate_escrows_start_at_upgrade_block() {
+	new_test_ext().execute_with(|| {
+		run_to_block(5);
+		StorageVersion::<Test>::put(Releases::V10);
+		unhashed::put(&EscrowedPurchases::<Test>::hashed_key_for(1), &(2u64, 100u64));
+
+		Kitties::on_runtime_upgrade();
+
+		assert_eq!(Kitties::escrowed_purchase(1), Some((2, 100, 5)));
+		assert_eq!(StorageVersion::<Test>::get(), Releases::V11);
+	});
+}
+
+#[test]
+fn generation_counts_follow_creates_breeds_and_burns() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_eq!(Kitties::generation_count(0), 2);
+
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 3));
+		assert_eq!(Kitties::generation_count(0), 2);
+		assert_eq!(Kitties::generation_count(1), 1);
+		assert_eq!(Kitties::generation_count(2), 1);
+
+		assert_ok!(Kitties::burn(Origin::signed(1), 4));
+		assert_ok!(Kitties::burn(Origin::signed(1), 1));
+		assert_eq!(Kitties::generation_count(0), 1);
+		assert_eq!(Kitties::generation_count(2), 0);
+	});
+}
+
+#[cfg(feature = "try-runtime")]
+#[test]
+fn migrate_keeps_ownership_invariants() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::<Test>::put(Releases::V4);
+		Owner::<Test>::insert(1, 3);
+		Owner::<Test>::insert(2, 3);
+		Owner::<Test>::insert(3, 4);
+
+		assert_ok!(Kitties::pre_upgrade());
+		Kitties::on_runtime_upgrade();
+		assert_ok!(Kitties::post_upgrade());
+
+		OwnedKittiesCount::<Test>::insert(3, 5);
+		assert!(Kitties::post_upgrade().is_err());
+	});
+}
+
+#[test]
+fn claim_abandoned_after_period() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_eq!(Kitties::last_activity(1), Some(1));
+
+		run_to_block(101);
+		assert_ok!(Kitties::claim_abandoned(Origin::signed(2), 1));
+
+		assert_eq!(Kitties::owner(1), Some(2));
+		assert_eq!(Kitties::kitties_price(1), None);
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 500);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 1_000_000_000);
+		assert_event!(Event::KittyClaimed(2, 1));
+	});
+}
+
+#[test]
+fn cattery_add_and_remove_works() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_ok!(Kitties::create_cattery(Origin::signed(1), b"home".to_vec()));
+		assert_event!(Event::CatteryCreated(1, 0));
+		assert_eq!(Kitties::next_cattery_id(), 1);
+
+		assert_ok!(Kitties::add_to_cattery(Origin::signed(1), 0, 1));
+		assert_ok!(Kitties::add_to_cattery(Origin::signed(1), 0, 2));
+		assert_event!(Event::KittyAddedToCattery(0, 2));
+		assert_noop!(
+			Kitties::add_to_cattery(Origin::signed(1), 0, 2),
+			Error::<Test>::AlreadyInCattery
+		);
+		assert_noop!(Kitties::add_to_cattery(Origin::signed(1), 0, 3), Error::<Test>::CatteryFull);
+
+		assert_ok!(Kitties::remove_from_cattery(Origin::signed(1), 0, 1));
+		assert_event!(Event::KittyRemovedFromCattery(0, 1));
+		assert_noop!(
+			Kitties::remove_from_cattery(Origin::signed(1), 0, 1),
+			Error::<Test>::NotInCattery
+		);
+
+		let (owner, name, kitties) = Kitties::catteries(0).unwrap();
+		assert_eq!(owner, 1);
+		assert_eq!(name.into_inner(), b"home".to_vec());
+		assert_eq!(kitties.into_inner(), vec![2]);
+	});
+}
+
+#[test]
+fn cattery_deposit_and_membership_follow_kitties() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		let reserved = Balances::reserved_balance(1);
+
+		assert_ok!(Kitties::create_cattery(Origin::signed(1), b"home".to_vec()));
+		assert_eq!(Balances::reserved_balance(1), reserved + 500);
+		assert_ok!(Kitties::create_cattery(Origin::signed(1), b"away".to_vec()));
+		assert_ok!(Kitties::add_to_cattery(Origin::signed(1), 0, 1));
+		assert_ok!(Kitties::add_to_cattery(Origin::signed(1), 0, 2));
+		assert_noop!(
+			Kitties::add_to_cattery(Origin::signed(1), 1, 1),
+			Error::<Test>::AlreadyInCattery
+		);
+
+		// 转让和销毁的 Kitty 自动移出 Cattery
+		assert_ok!(Kitties::transfer(Origin::signed(1), 2, 1));
+		assert_event!(Event::KittyRemovedFromCattery(0, 1));
+		assert_eq!(Kitties::cattery_of(1), None);
+		assert_ok!(Kitties::burn(Origin::signed(1), 2));
+		assert!(Kitties::catteries(0).unwrap().2.is_empty());
+
+		assert_noop!(Kitties::remove_cattery(Origin::signed(2), 0), Error::<Test>::NotCatteryOwner);
+		assert_ok!(Kitties::remove_cattery(Origin::signed(1), 0));
+		assert_event!(Event::CatteryRemoved(1, 0));
+		assert_eq!(Kitties::catteries(0), None);
+		assert_eq!(Balances::reserved_balance(1), reserved - 2_000_000_000 + 500);
+	});
+}
+
+#[test]
+fn cattery_rejects_kitties_of_others() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		assert_ok!(Kitties::create_cattery(Origin::signed(1), b"home".to_vec()));
+
+		assert_noop!(
+			Kitties::add_to_cattery(Origin::signed(1), 0, 2),
+			Error::<Test>::NotOwnerOfKitty
+		);
+		assert_noop!(
+			Kitties::add_to_cattery(Origin::signed(2), 0, 2),
+			Error::<Test>::NotCatteryOwner
+		);
+		assert_noop!(
+			Kitties::add_to_cattery(Origin::signed(1), 1, 1),
+			Error::<Test>::CatteryNotFound
+		);
+	});
+}
+
+#[test]
+fn record_views_only_by_oracle() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_noop!(
+			Kitties::record_views(Origin::signed(1), vec![(1, 10)]),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Kitties::record_views(Origin::root(), vec![(1, 10), (9, 1)]),
+			Error::<Test>::InvalidKittyIndex
+		);
+		assert_noop!(
+			Kitties::record_views(Origin::root(), vec![(1, 1); 4]),
+			Error::<Test>::TooManyKitties
+		);
+
+		assert_ok!(Kitties::record_views(Origin::root(), vec![(1, 10), (2, 3)]));
+		assert_ok!(Kitties::record_views(Origin::root(), vec![(1, 5)]));
+		assert_event!(Event::ViewsRecorded(1));
+		assert_eq!(Kitties::view_count(1), 15);
+		assert_eq!(Kitties::view_count(2), 3);
+	});
+}
+
+#[test]
+fn withdraw_fees_works() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(100)));
+
+		run_to_block(101);
+		assert_ok!(Kitties::claim_abandoned(Origin::signed(2), 1));
+		assert_ok!(Kitties::initiate_buy(Origin::signed(3), 2));
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 600);
+
+		// 托管中的货款不能提取
+		assert_eq!(Kitties::withdrawable_fees(), 500);
+		assert_noop!(
+			Kitties::withdraw_fees(Origin::root(), 4, 501),
+			Error::<Test>::InsufficientTreasuryBalance
+		);
+		assert_noop!(Kitties::withdraw_fees(Origin::signed(1), 4, 100), DispatchError::BadOrigin);
+
+		assert_ok!(Kitties::withdraw_fees(Origin::root(), 4, 500));
+		assert_event!(Event::FeesWithdrawn(4, 500));
+		assert_eq!(Balances::free_balance(4), 500);
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 100);
+		assert_eq!(Kitties::withdrawable_fees(), 0);
+
+		assert_ok!(Kitties::cancel_buy(Origin::signed(3), 2));
+		assert_eq!(Balances::free_balance(Kitties::account_id()), 0);
+	});
+}
+
+#[test]
+fn claim_abandoned_failed_before_period() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+
+		run_to_block(50);
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+		assert_ok!(Kitties::unlock(Origin::signed(1), 1));
+
+		run_to_block(101);
+		assert_noop!(Kitties::claim_abandoned(Origin::signed(2), 1), Error::<Test>::NotAbandoned);
+
+		run_to_block(150);
+		assert_ok!(Kitties::claim_abandoned(Origin::signed(2), 1));
+	});
+}
+
+#[test]
+fn compatibility_from_dna() {
+	new_test_ext().execute_with(|| {
+		crate::Kitties::<Test>::insert(1, Some(Kitty(vec![0xAA; 16])));
+		crate::Kitties::<Test>::insert(2, Some(Kitty(vec![0xAA; 16])));
+		crate::Kitties::<Test>::insert(3, Some(Kitty(vec![0x55; 16])));
+		crate::Kitties::<Test>::insert(4, Some(Kitty(vec![0xA5; 16])));
+
+		assert_eq!(Kitties::compatibility(1, 2), Some(0));
+		assert_eq!(Kitties::compatibility(1, 3), Some(100));
+		assert_eq!(Kitties::compatibility(1, 4), Some(50));
+		assert_eq!(Kitties::compatibility(1, 5), None);
+	});
+}
+
+#[test]
+fn breed_cooldown_scales_with_generation() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_ok!(Kitties::breed(Origin::signed(1), 3, 1));
+		assert_eq!(Kitties::generation(4), 2);
+
+		BaseBreedCooldown::set(10);
+		run_to_block(2);
+		assert_ok!(Kitties::breed(Origin::signed(1), 4, 2));
+
+		assert_eq!(Kitties::breed_cooldown_until(2), Some(12));
+		assert_eq!(Kitties::breed_cooldown_until(4), Some(32));
+
+		run_to_block(12);
+		assert_noop!(Kitties::breed(Origin::signed(1), 4, 1), Error::<Test>::BreedCooldownActive);
+		assert_ok!(Kitties::breed(Origin::signed(1), 2, 1));
+
+		run_to_block(32);
+		assert_ok!(Kitties::breed(Origin::signed(1), 4, 3));
+	});
+}
+
+#[test]
+fn breed_consumes_potion() {
+	new_test_ext().execute_with(|| {
+		RequireBreedPotion::set(true);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		assert_noop!(
+			Kitties::breed(Origin::signed(1), 1, 2),
+			DispatchError::Other("NoBreedPotion")
+		);
+
+		give_breed_potions(1, 1);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_eq!(breed_potions(1), 0);
+		assert_eq!(Kitties::owner(3), Some(1));
+	});
+}
+
+#[test]
+fn breed_requires_min_age() {
+	new_test_ext().execute_with(|| {
+		MinBreedAge::set(5);
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+
+		run_to_block(5);
+		assert_noop!(Kitties::breed(Origin::signed(1), 1, 2), Error::<Test>::KittyTooYoung);
+
+		run_to_block(6);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+
+		// 刚出生的孩子不能马上生产
+		assert_noop!(Kitties::breed(Origin::signed(1), 3, 1), Error::<Test>::KittyTooYoung);
+	});
+}
+
+#[test]
+fn kitties_info_preserves_order() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(2);
+		assert_ok!(Kitties::sell(Origin::signed(2), 2, Some(100)));
+
+		let info = Kitties::kitties_info(vec![2, 5, 1, 2]);
+
+		// 最多查询 MaxBatchSize 个
+		assert_eq!(info.len(), 3);
+		assert_eq!(
+			info[0],
+			Some(KittyInfo {
+				dna: dna_of(2).unwrap(),
+				owner: 2,
+				price: Some((0, 100)),
+				generation: 0
+			})
+		);
+		assert_eq!(info[1], None);
+		assert_eq!(
+			info[2],
+			Some(KittyInfo { dna: dna_of(1).unwrap(), owner: 1, price: None, generation: 0 })
+		);
+	});
+}
+
+#[test]
+fn count_warning_fires_once() {
+	new_test_ext().execute_with(|| {
+		CountWarningThreshold::set(2);
+		let _ = new_kitty(1);
+		assert!(!Kitties::warning_emitted());
+
+		let _ = new_kitty(1);
+		assert_event!(Event::CountThresholdReached(2));
+		assert!(Kitties::warning_emitted());
+
+		let _ = new_kitty(1);
+		assert_ok!(Kitties::set_kitties_count(Origin::root(), 10));
+		let warnings = System::events()
+			.iter()
+			.filter(|record| {
+				matches!(record.event, TestEvent::Kitties(Event::CountThresholdReached(_)))
+			})
+			.count();
+		assert_eq!(warnings, 1);
+	});
+}
+
+#[test]
+fn kitty_record_matches_storage() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		run_to_block(5);
+		assert_ok!(Kitties::breed(Origin::signed(1), 1, 2));
+		assert_ok!(Kitties::set_name(Origin::signed(1), 3, b"tom".to_vec()));
+		assert_ok!(Kitties::sell(Origin::signed(1), 3, Some(100)));
+		assert_ok!(Kitties::lock(Origin::signed(1), 1));
+
+		assert_eq!(
+			Kitties::kitty_record(3),
+			Some(KittyRecord {
+				dna: dna_of(3).unwrap(),
+				owner: 1,
+				generation: 1,
+				parents: Some((1, 2)),
+				price: Some((0, 100)),
+				name: Some(b"tom".to_vec()),
+				locked: false,
+				created_at: Some(5),
+			})
+		);
+
+		let record = Kitties::kitty_record(1).unwrap();
+		assert_eq!(record.parents, None);
+		assert_eq!(record.name, None);
+		assert!(record.locked);
+		assert_eq!(record.created_at, Some(1));
+
+		assert_eq!(Kitties::kitty_record(9), None);
+	});
+}
+
+#[test]
+fn market_stats_accumulate_sales() {
+	new_test_ext().execute_with(|| {
+		let _ = new_kitty(1);
+		let _ = new_kitty(1);
+		assert_eq!(Kitties::market_stats(), (0, 0));
+
+		assert_ok!(Kitties::sell(Origin::signed(1), 1, Some(100)));
+		assert_ok!(Kitties::sell(Origin::signed(1), 2, Some(250)));
+		assert_ok!(Kitties::buy(Origin::signed(2), 1, None));
+		assert_ok!(Kitties::buy(Origin::signed(2), 2, None));
+		assert_eq!(Kitties::market_stats(), (2, 350));
+
+		run_to_block(11);
+		assert_ok!(Kitties::sell(Origin::signed(2), 1, Some(50)));
+		assert_ok!(Kitties::buy(Origin::signed(1), 1, None));
+		assert_eq!(Kitties::total_sales_count(), 3);
+		assert_eq!(Kitties::total_volume(), 400);
+	});
+}
+
+#[test]
+fn market_params_match_config() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(
+			Kitties::market_params(),
+			MarketParams {
+				decimals: 12,
+				reserve: 1_000_000_000,
+				attribute_deposit: 10_000,
+				claim_fee: 500,
+				sale_cooldown: 10,
+			}
+		);
+
+		RequireDeposit::set(false);
+		assert_eq!(Kitties::market_params().reserve, 0);
 	});
 }
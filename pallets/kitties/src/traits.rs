@@ -0,0 +1,143 @@
+//! Kitties Pallet 对外提供的扩展点
+
+use frame_support::{dispatch::DispatchResult, sp_std::prelude::*};
+
+/// 生产 Kitty 时混合父母 DNA 的算法
+pub trait BreedMixer {
+	/// 混合父母的 DNA
+	/// ### Arguments
+	/// * `dna_1` - 父亲的 DNA
+	/// * `dna_2` - 母亲的 DNA
+	/// * `selector` - 生产时的随机数, 与 DNA 等长
+	fn mix(dna_1: &[u8], dna_2: &[u8], selector: &[u8]) -> Vec<u8>;
+}
+
+/// 默认的混合算法
+/// selector 中为 1 的位取自父亲, 为 0 的位取自母亲
+/// 父母 DNA 长度不同时, 孩子的长度取最短的一个
+pub struct BitwiseBlend;
+
+impl BreedMixer for BitwiseBlend {
+	fn mix(dna_1: &[u8], dna_2: &[u8], selector: &[u8]) -> Vec<u8> {
+		dna_1
+			.iter()
+			.zip(dna_2)
+			.zip(selector)
+			.map(|((gene_1, gene_2), select)| (select & gene_1) | (!select & gene_2))
+			.collect()
+	}
+}
+
+/// Kitty 生命周期的回调, 供其他 Pallet 在 Kitty 变化时处理自己的逻辑
+/// 不需要时使用 `()`
+pub trait KittyHooks<AccountId, KittyIndex> {
+	/// Kitty 被创建或生产后调用
+	/// ### Arguments
+	/// * `owner` - Kitty 的主人
+	/// * `kitty_id` - 新的 Kitty 编号
+	fn on_created(owner: &AccountId, kitty_id: KittyIndex);
+
+	/// Kitty 转移后调用
+	/// ### Arguments
+	/// * `from` - 原来的主人
+	/// * `to` - 新的主人
+	/// * `kitty_id` - 转移的 Kitty 编号
+	fn on_transferred(from: &AccountId, to: &AccountId, kitty_id: KittyIndex);
+
+	/// Kitty 销毁后调用
+	/// ### Arguments
+	/// * `owner` - 销毁前的主人
+	/// * `kitty_id` - 销毁的 Kitty 编号
+	fn on_burned(owner: &AccountId, kitty_id: KittyIndex);
+}
+
+impl<AccountId, KittyIndex> KittyHooks<AccountId, KittyIndex> for () {
+	fn on_created(_owner: &AccountId, _kitty_id: KittyIndex) {}
+
+	fn on_transferred(_from: &AccountId, _to: &AccountId, _kitty_id: KittyIndex) {}
+
+	fn on_burned(_owner: &AccountId, _kitty_id: KittyIndex) {}
+}
+
+/// 检查出售价格, 用于实现动态的价格规则
+/// 不需要时使用 `()`, 接受所有价格
+pub trait ValidatePrice<Balance, KittyIndex> {
+	/// 检查出售价格, 返回错误时不能出售
+	/// ### Arguments
+	/// * `kitty_id` - 出售的 Kitty 编号
+	/// * `price` - 出售价格
+	fn validate(kitty_id: KittyIndex, price: Balance) -> DispatchResult;
+}
+
+impl<Balance, KittyIndex> ValidatePrice<Balance, KittyIndex> for () {
+	fn validate(_kitty_id: KittyIndex, _price: Balance) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// 生成 DNA 时使用的哈希算法
+pub trait DnaHasher {
+	/// 计算哈希, 结果不能为空
+	/// ### Arguments
+	/// * `data` - 被哈希的数据
+	fn hash(data: &[u8]) -> Vec<u8>;
+}
+
+/// 默认的哈希算法 blake2_128
+pub struct Blake2Dna;
+
+impl DnaHasher for Blake2Dna {
+	fn hash(data: &[u8]) -> Vec<u8> {
+		sp_io::hashing::blake2_128(data).to_vec()
+	}
+}
+
+/// twox_128 哈希算法
+pub struct TwoxDna;
+
+impl DnaHasher for TwoxDna {
+	fn hash(data: &[u8]) -> Vec<u8> {
+		sp_io::hashing::twox_128(data).to_vec()
+	}
+}
+
+/// keccak_256 哈希算法
+pub struct KeccakDna;
+
+impl DnaHasher for KeccakDna {
+	fn hash(data: &[u8]) -> Vec<u8> {
+		sp_io::hashing::keccak_256(data).to_vec()
+	}
+}
+
+/// 生产时消耗的道具, 例如由物品 Pallet 销毁一瓶生产药水
+/// 不需要时使用 `()`
+pub trait Consumable<AccountId> {
+	/// 消耗账户的一个道具, 返回错误时不能生产
+	/// ### Arguments
+	/// * `who` - 生产者
+	fn consume(who: &AccountId) -> DispatchResult;
+}
+
+impl<AccountId> Consumable<AccountId> for () {
+	fn consume(_who: &AccountId) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// 转移 Kitty 前的外部审批, 例如 KYC 或合规检查
+/// 不需要时使用 `()`, 允许所有转移
+pub trait AuthorizeTransfer<AccountId, KittyIndex> {
+	/// 检查转移, 返回错误时不能转移
+	/// ### Arguments
+	/// * `from` - 原来的主人
+	/// * `to` - 新的主人
+	/// * `kitty_id` - 转移的 Kitty 编号
+	fn authorize(from: &AccountId, to: &AccountId, kitty_id: KittyIndex) -> DispatchResult;
+}
+
+impl<AccountId, KittyIndex> AuthorizeTransfer<AccountId, KittyIndex> for () {
+	fn authorize(_from: &AccountId, _to: &AccountId, _kitty_id: KittyIndex) -> DispatchResult {
+		Ok(())
+	}
+}
@@ -31,7 +31,7 @@ pub use frame_support::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
@@ -57,6 +57,9 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Identifier of an asset that kitties can be listed in. Asset 0 stands for the native balance.
+pub type AssetId = u32;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -99,7 +102,9 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	// Bump it whenever a pallet adds a storage migration, otherwise `on_runtime_upgrade` never
+	//   runs on a live chain.
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -253,6 +258,48 @@ impl pallet_balances::Config for Runtime {
 parameter_types! {
 	pub const TransactionByteFee: Balance = 1;
 	pub const ReserveOfNewCreate: u32 = 1_000;
+	pub const DnaLength: u32 = 16;
+	pub const RestrictMinting: bool = false;
+	pub const CompactEvents: bool = false;
+	pub const RequireDeposit: bool = true;
+	pub const BreedingEnabled: bool = true;
+	pub const RequireMatchingBreeds: bool = false;
+	pub const InheritanceBias: u8 = 128;
+	pub const FusionEnabled: bool = false;
+	pub TwinRate: Permill = Permill::from_percent(1);
+	pub TraitMutationRate: Permill = Permill::from_percent(2);
+	pub const MaxBreedsPerBlock: u32 = 100;
+	pub const BaseBreedCooldown: BlockNumber = 10 * MINUTES;
+	pub const MinBreedAge: BlockNumber = HOURS;
+	pub const MaxKeyLen: u32 = 32;
+	pub const MaxValueLen: u32 = 256;
+	pub const AttributeDeposit: Balance = 100;
+	pub const SaleCooldown: BlockNumber = 10 * MINUTES;
+	pub const MaxBatchSize: u32 = 50;
+	pub const MaxKittiesPerAccount: u32 = 1_000;
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+	pub const AbandonmentPeriod: BlockNumber = 180 * DAYS;
+	pub const AbandonmentClaimFee: Balance = 1_000;
+	pub const BalanceDecimals: u8 = 12;
+	pub const MaxNameLength: u32 = 32;
+	pub const RequireRecipientExists: bool = false;
+	pub const RequireUniqueNames: bool = false;
+	pub const EmitTransferEvents: bool = true;
+	pub const MaxDepositPerAccount: Balance = 1_000_000;
+	pub const LegendaryBypassCooldown: bool = false;
+	pub const BreedReward: Balance = 10;
+	pub const MaxBreedRewards: Balance = 1_000_000;
+	pub const CountWarningThreshold: u32 = u32::MAX / 10 * 9;
+	pub const BreedFee: Balance = 0;
+	pub BreedCostScaling: Permill = Permill::from_percent(10);
+	pub const RecentWindow: u32 = 20;
+	pub const MaxCatterySize: u32 = 100;
+	pub const MaxBreedsPerAccountPerBlock: u32 = 5;
+	pub const EscrowPeriod: BlockNumber = 3 * DAYS;
+	pub const MaxLockExpiriesPerBlock: u32 = 256;
+	pub const CatteryDeposit: Balance = 100;
+	pub const AllowBreedingListed: bool = false;
+	pub const PostAcquireTransferCooldown: BlockNumber = 10 * MINUTES;
 }
 
 impl pallet_transaction_payment::Config for Runtime {
@@ -275,9 +322,62 @@ impl pallet_template::Config for Runtime {
 impl pallet_kitties::Config for Runtime {
 	type Event = Event;
 	type Randomness = RandomnessCollectiveFlip;
+	type SecondaryRandomness = pallet_kitties::NoRandomness;
+	type DnaLength = DnaLength;
+	type CompactEvents = CompactEvents;
 	type KittyIndex = u32;
 	type ReserveOfNewCreate = ReserveOfNewCreate;
+	type RequireDeposit = RequireDeposit;
+	type RestrictMinting = RestrictMinting;
 	type Currency = Balances;
+	type BreedingEnabled = BreedingEnabled;
+	type TwinRate = TwinRate;
+	type TraitMutationRate = TraitMutationRate;
+	type InheritanceBias = InheritanceBias;
+	type MaxBreedsPerBlock = MaxBreedsPerBlock;
+	type BaseBreedCooldown = BaseBreedCooldown;
+	type MinBreedAge = MinBreedAge;
+	type RequireMatchingBreeds = RequireMatchingBreeds;
+	type FusionEnabled = FusionEnabled;
+	type BreedAlgorithm = pallet_kitties::traits::BitwiseBlend;
+	type DnaHasher = pallet_kitties::traits::Blake2Dna;
+	type OnKittyEvent = ();
+	type PriceValidator = ();
+	type BreedConsumable = ();
+	type TransferAuthorizer = ();
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxKeyLen = MaxKeyLen;
+	type MaxValueLen = MaxValueLen;
+	type AttributeDeposit = AttributeDeposit;
+	type SaleCooldown = SaleCooldown;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxKittiesPerAccount = MaxKittiesPerAccount;
+	type PalletId = KittiesPalletId;
+	type AbandonmentPeriod = AbandonmentPeriod;
+	type AbandonmentClaimFee = AbandonmentClaimFee;
+	type BalanceDecimals = BalanceDecimals;
+	type MaxNameLength = MaxNameLength;
+	type RequireRecipientExists = RequireRecipientExists;
+	type RequireUniqueNames = RequireUniqueNames;
+	type EmitTransferEvents = EmitTransferEvents;
+	type MaxDepositPerAccount = MaxDepositPerAccount;
+	type LegendaryBypassCooldown = LegendaryBypassCooldown;
+	type AssetId = AssetId;
+	type Assets = pallet_kitties::NoAssets<AssetId, Balance>;
+	type BreedReward = BreedReward;
+	type MaxBreedRewards = MaxBreedRewards;
+	type CountWarningThreshold = CountWarningThreshold;
+	type BreedFee = BreedFee;
+	type BreedCostScaling = BreedCostScaling;
+	type RecentWindow = RecentWindow;
+	type AllowBreedingListed = AllowBreedingListed;
+	type PostAcquireTransferCooldown = PostAcquireTransferCooldown;
+	type ViewOracle = frame_system::EnsureRoot<AccountId>;
+	type MaxCatterySize = MaxCatterySize;
+	type MaxBreedsPerAccountPerBlock = MaxBreedsPerAccountPerBlock;
+	type EscrowPeriod = EscrowPeriod;
+	type MaxLockExpiriesPerBlock = MaxLockExpiriesPerBlock;
+	type CatteryDeposit = CatteryDeposit;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -297,7 +397,7 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
-		Kitties: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		Kitties: pallet_kitties::{Pallet, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
@@ -440,6 +540,40 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_runtime_api::KittiesApi<
+		Block,
+		AccountId,
+		Balance,
+		u32,
+		pallet_kitties::KittyInfo<AccountId, AssetId, Balance>,
+		pallet_kitties::KittyRecord<AccountId, AssetId, Balance, BlockNumber, u32>,
+		pallet_kitties::MarketParams<Balance, BlockNumber>,
+	> for Runtime {
+		fn kitties_by_owner(owner: AccountId) -> Vec<u32> {
+			Kitties::kitties_of(&owner)
+		}
+
+		fn kitties_info(
+			kitty_ids: Vec<u32>,
+		) -> Vec<Option<pallet_kitties::KittyInfo<AccountId, AssetId, Balance>>> {
+			Kitties::kitties_info(kitty_ids)
+		}
+
+		fn kitty_record(
+			kitty_id: u32,
+		) -> Option<pallet_kitties::KittyRecord<AccountId, AssetId, Balance, BlockNumber, u32>> {
+			Kitties::kitty_record(kitty_id)
+		}
+
+		fn market_params() -> pallet_kitties::MarketParams<Balance, BlockNumber> {
+			Kitties::market_params()
+		}
+
+		fn market_stats() -> (u32, Balance) {
+			Kitties::market_stats()
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,